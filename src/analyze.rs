@@ -1,3 +1,8 @@
+use crate::analyzer::DatasetColumns;
+use crate::formats::{ColumnType, Dataset};
+use crate::sample::SampleInfo;
+use serde::{Deserialize, Serialize};
+
 /// Automatically detect column type: numeric, categorical, boolean, date
 pub fn detect_column_type(col: &[String]) -> &'static str {
     let is_bool = col
@@ -40,6 +45,23 @@ pub fn pearson_correlation(x: &[f64], y: &[f64]) -> Option<f64> {
     }
 }
 
+/// Pearson correlation of every pair of non-empty numeric columns, as
+/// `(i, j, r)` with `i < j`, skipping constant pairs. Pairs are computed in
+/// parallel and come back in row-major order.
+pub fn correlation_pairs(num_columns: &[Vec<f64>]) -> Vec<(usize, usize, f64)> {
+    use rayon::prelude::*;
+    let pairs: Vec<(usize, usize)> = (0..num_columns.len())
+        .flat_map(|i| ((i + 1)..num_columns.len()).map(move |j| (i, j)))
+        .filter(|&(i, j)| !num_columns[i].is_empty() && !num_columns[j].is_empty())
+        .collect();
+    pairs
+        .into_par_iter()
+        .filter_map(|(i, j)| {
+            pearson_correlation(&num_columns[i], &num_columns[j]).map(|r| (i, j, r))
+        })
+        .collect()
+}
+
 /// Suggest cleaning actions for a numeric column
 pub fn cleaning_suggestions(col: &[f64]) -> Vec<String> {
     let mut suggestions = Vec::new();
@@ -69,7 +91,7 @@ pub fn mean(data: &[f64]) -> f64 {
 
 pub fn median(data: &[f64]) -> f64 {
     let mut sorted = data.to_vec();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted.sort_by(f64::total_cmp);
     let len = sorted.len();
     if len.is_multiple_of(2) {
         (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0
@@ -78,22 +100,26 @@ pub fn median(data: &[f64]) -> f64 {
     }
 }
 
+/// Most frequent value; ties go to the value seen first
 pub fn mode(data: &[String]) -> String {
     use std::collections::HashMap;
     let mut counts = HashMap::new();
     for val in data {
         *counts.entry(val).or_insert(0) += 1;
     }
-    counts
-        .into_iter()
-        .max_by_key(|(_, count)| *count)
-        .map(|(val, _)| val.clone())
-        .unwrap_or_default()
+    let mut best: Option<(&String, usize)> = None;
+    for val in data {
+        let count = counts[val];
+        if best.is_none_or(|(_, best_count)| count > best_count) {
+            best = Some((val, count));
+        }
+    }
+    best.map(|(val, _)| val.clone()).unwrap_or_default()
 }
 
 pub fn quartiles(data: &[f64]) -> (f64, f64) {
     let mut sorted = data.to_vec();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted.sort_by(f64::total_cmp);
     let len = sorted.len();
     let q1_idx = len as f64 * 0.25;
     let q3_idx = len as f64 * 0.75;
@@ -101,3 +127,282 @@ pub fn quartiles(data: &[f64]) -> (f64, f64) {
     let q3 = sorted[q3_idx.floor() as usize];
     (q1, q3)
 }
+
+/// Whether a cell counts as missing (empty or whitespace only)
+pub fn is_missing(value: &str) -> bool {
+    value.trim().is_empty()
+}
+
+/// Type of a whole column ignoring missing cells; "empty" when no value is present
+pub fn infer_column_type(values: &[String]) -> &'static str {
+    let present: Vec<String> = values.iter().filter(|v| !is_missing(v)).cloned().collect();
+    if present.is_empty() {
+        "empty"
+    } else {
+        detect_column_type(&present)
+    }
+}
+
+/// Suggest cleaning actions for a raw column of any type: missing cells, values
+/// that break an otherwise numeric column, and numeric outliers
+pub fn column_cleaning_suggestions(values: &[String]) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    let missing = values.iter().filter(|v| is_missing(v)).count();
+    if missing > 0 {
+        suggestions.push(format!("{} missing values detected", missing));
+    }
+    let present: Vec<&String> = values.iter().filter(|v| !is_missing(v)).collect();
+    let numbers: Vec<f64> = present.iter().filter_map(|v| v.trim().parse().ok()).collect();
+    let non_numeric = present.len() - numbers.len();
+    if !numbers.is_empty() && non_numeric > 0 && numbers.len() > non_numeric {
+        suggestions.push(format!(
+            "{} non-numeric values in a mostly numeric column",
+            non_numeric
+        ));
+    }
+    if numbers.len() == present.len() && !numbers.is_empty() {
+        suggestions.extend(
+            cleaning_suggestions(&numbers)
+                .into_iter()
+                .filter(|s| s != "No cleaning needed"),
+        );
+    }
+    if suggestions.is_empty() {
+        suggestions.push("No cleaning needed".to_string());
+    }
+    suggestions
+}
+
+/// The whole analysis of a dataset, computed once and read by every report
+/// renderer (HTML, Markdown, PDF, interactive HTML and the JSON profile, whose
+/// layout is described by schema/profile.schema.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub schema_version: u32,
+    /// Local time of the analysis (YYYY-MM-DD HH:MM)
+    pub generated_at: String,
+    /// Where the data came from, as shown in reports
+    pub source: String,
+    pub row_count: usize,
+    pub column_count: usize,
+    /// The sample the statistics were computed from, when they were
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<SampleSummary>,
+    pub correlations: Vec<Correlation>,
+    /// Numeric parts of columns first, then the non-numeric parts, each in
+    /// column order
+    pub columns: Vec<ColumnProfile>,
+    /// Findings of analyzers about the whole dataset
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<ReportSection>,
+}
+
+/// Pearson correlation of two numeric columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correlation {
+    pub a: String,
+    pub b: String,
+    pub r: f64,
+}
+
+/// The analysis of the numeric or the non-numeric values of one column;
+/// exactly one of `numeric` and `categorical` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    /// Position of the column in the dataset
+    pub index: usize,
+    pub name: String,
+    pub col_type: String,
+    pub count: usize,
+    pub numeric: Option<NumericStats>,
+    pub categorical: Option<CategoricalStats>,
+    pub suggestions: Vec<String>,
+    /// Findings of analyzers about this column
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<ReportSection>,
+}
+
+/// A titled list of findings an analyzer adds to reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSection {
+    /// Name of the analyzer that produced it
+    pub analyzer: String,
+    pub title: String,
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumericStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub iqr: f64,
+    pub outliers: Vec<f64>,
+    /// Number of distinct values
+    #[serde(default)]
+    pub unique: usize,
+    /// Half-width of the 95% confidence interval of the mean, for random samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_margin: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoricalStats {
+    pub unique: usize,
+    pub mode: String,
+    pub mode_count: usize,
+    /// Value counts, most frequent first
+    pub frequencies: Vec<ValueCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+/// How the rows were sampled, with a sentence saying so
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleSummary {
+    pub description: String,
+    #[serde(flatten)]
+    pub info: SampleInfo,
+}
+
+/// Analyse a dataset: column statistics, frequency tables, correlations and
+/// cleaning suggestions, with error bars on the means of a random sample.
+/// `source` is left empty; see [`Profile::with_source`].
+pub fn profile(dataset: &Dataset) -> Profile {
+    let (num_columns, cat_columns) = dataset.split_columns();
+    profile_columns(
+        &num_columns,
+        &cat_columns,
+        &dataset.headers,
+        dataset.types.as_deref(),
+        dataset.sample.as_ref(),
+    )
+}
+
+/// Analyse columns already split into their numeric and non-numeric values
+/// (see [`Dataset::split_columns`]) with the registered analyzers (see
+/// [`crate::analyzer`]), one column per worker thread. `types` are the
+/// dataset's schema types, if any.
+pub fn profile_columns(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    types: Option<&[ColumnType]>,
+    sample: Option<&SampleInfo>,
+) -> Profile {
+    use rayon::prelude::*;
+    let analyzers = crate::analyzer::analyzers();
+    let dataset = DatasetColumns {
+        headers,
+        num_columns,
+        cat_columns,
+        types,
+        sample,
+    };
+    let columns = dataset
+        .columns()
+        .par_iter()
+        .map(|column| {
+            let mut profile = ColumnProfile {
+                index: column.index,
+                name: column.name.to_string(),
+                col_type: column.col_type.to_string(),
+                count: column.values.len(),
+                numeric: None,
+                categorical: None,
+                suggestions: Vec::new(),
+                sections: Vec::new(),
+            };
+            for analyzer in &analyzers {
+                analyzer.analyze_column(column, &mut profile);
+            }
+            if profile.numeric.is_some() && profile.suggestions.is_empty() {
+                profile.suggestions.push("No cleaning needed".to_string());
+            }
+            profile
+        })
+        .collect();
+    // Every cell lands in exactly one of the two splits of its column
+    let row_count = (0..headers.len())
+        .map(|i| {
+            num_columns.get(i).map_or(0, |c| c.len()) + cat_columns.get(i).map_or(0, |c| c.len())
+        })
+        .max()
+        .unwrap_or(0);
+    let mut profile = Profile {
+        schema_version: crate::profile::PROFILE_SCHEMA_VERSION,
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        source: String::new(),
+        row_count,
+        column_count: headers.len(),
+        sample: sample.map(|s| SampleSummary {
+            description: s.description(),
+            info: s.clone(),
+        }),
+        correlations: Vec::new(),
+        columns,
+        sections: Vec::new(),
+    };
+    for analyzer in &analyzers {
+        analyzer.analyze_dataset(&dataset, &mut profile);
+    }
+    profile
+}
+
+impl Profile {
+    /// Name the data the profile describes
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = source.to_string();
+        self
+    }
+}
+
+/// Statistics of numeric values, with an error bar on the mean of a random
+/// sample; outliers are left to the outlier analyzer
+pub fn numeric_stats(col: &[f64], sample: Option<&SampleInfo>) -> NumericStats {
+    let (q1, q3) = quartiles(col);
+    let unique: std::collections::HashSet<u64> = col.iter().map(|v| v.to_bits()).collect();
+    NumericStats {
+        min: col.iter().cloned().fold(f64::INFINITY, f64::min),
+        max: col.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+        mean: mean(col),
+        median: median(col),
+        q1,
+        q3,
+        iqr: q3 - q1,
+        outliers: Vec::new(),
+        unique: unique.len(),
+        mean_margin: sample.and_then(|s| s.mean_margin(col)),
+    }
+}
+
+/// Value counts and mode of non-numeric values
+pub fn categorical_stats(col: &[String]) -> CategoricalStats {
+    let mut counts: std::collections::HashMap<&String, usize> = std::collections::HashMap::new();
+    for v in col {
+        *counts.entry(v).or_insert(0) += 1;
+    }
+    let mut frequencies: Vec<ValueCount> = counts
+        .iter()
+        .map(|(value, count)| ValueCount {
+            value: value.to_string(),
+            count: *count,
+        })
+        .collect();
+    frequencies.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    let mode_val = mode(col);
+    let mode_count = counts.get(&mode_val).copied().unwrap_or(0);
+    CategoricalStats {
+        unique: counts.len(),
+        mode: mode_val,
+        mode_count,
+        frequencies,
+    }
+}
@@ -0,0 +1,260 @@
+// Pluggable analyses: every part of a profile comes from an `Analyzer` that
+// looks at one typed column or at the whole dataset. The built-in statistics,
+// outlier and correlation analyses are analyzers too; domain-specific checks
+// (country codes, personal data...) register next to them and add report
+// sections and cleaning suggestions.
+use crate::analyze::{
+    ColumnProfile, Correlation, Profile, categorical_stats, cleaning_suggestions,
+    correlation_pairs, numeric_stats,
+};
+use crate::formats::ColumnType;
+use crate::sample::SampleInfo;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// The values of one part of a column
+#[derive(Debug, Clone, Copy)]
+pub enum ColumnValues<'a> {
+    /// The values that parse as numbers
+    Numeric(&'a [f64]),
+    /// The other values
+    Text(&'a [String]),
+}
+
+impl ColumnValues<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnValues::Numeric(values) => values.len(),
+            ColumnValues::Text(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The numeric or the non-numeric values of a column, with its detected type
+#[derive(Debug, Clone, Copy)]
+pub struct TypedColumn<'a> {
+    /// Position of the column in the dataset
+    pub index: usize,
+    pub name: &'a str,
+    /// `numeric` for numeric values, else `categorical`, `boolean` or `date`
+    pub col_type: &'a str,
+    pub values: ColumnValues<'a>,
+    /// The sample the values were drawn from, when they were
+    pub sample: Option<&'a SampleInfo>,
+}
+
+/// Every column of a dataset, split into numeric and non-numeric values (see
+/// `Dataset::split_columns`)
+#[derive(Debug, Clone, Copy)]
+pub struct DatasetColumns<'a> {
+    pub headers: &'a [String],
+    pub num_columns: &'a [Vec<f64>],
+    pub cat_columns: &'a [Vec<String>],
+    /// Column types given by the file's schema, when it has one
+    pub types: Option<&'a [ColumnType]>,
+    pub sample: Option<&'a SampleInfo>,
+}
+
+impl<'a> DatasetColumns<'a> {
+    /// The non-empty typed columns: numeric parts first, then the
+    /// non-numeric ones, each in column order (the order of profile columns).
+    /// Non-numeric parts take the schema's column type when there is one;
+    /// the nulls of a numeric schema column make no part of their own.
+    pub fn columns(&self) -> Vec<TypedColumn<'a>> {
+        let schema_type = |i: usize| self.types.and_then(|types| types.get(i).copied());
+        let numeric = self.num_columns.iter().enumerate().map(|(i, col)| TypedColumn {
+            index: i,
+            name: &self.headers[i],
+            col_type: "numeric",
+            values: ColumnValues::Numeric(col),
+            sample: self.sample,
+        });
+        let text = self
+            .cat_columns
+            .iter()
+            .enumerate()
+            .filter(move |&(i, _)| {
+                schema_type(i) != Some(ColumnType::Numeric) || self.num_columns[i].is_empty()
+            })
+            .map(move |(i, col)| TypedColumn {
+                index: i,
+                name: &self.headers[i],
+                col_type: match schema_type(i) {
+                    Some(t) if t != ColumnType::Numeric => t.as_str(),
+                    _ => crate::analyze::detect_column_type(col),
+                },
+                values: ColumnValues::Text(col),
+                sample: self.sample,
+            });
+        numeric.chain(text).filter(|c| !c.values.is_empty()).collect()
+    }
+}
+
+/// An analysis run while a dataset is profiled. Column analyses run in
+/// parallel, one column per worker thread; the dataset analysis runs once all
+/// columns are profiled. Both add to the profile: statistics, cleaning
+/// suggestions or [`ReportSection`](crate::analyze::ReportSection)s shown in
+/// every report.
+pub trait Analyzer: Send + Sync {
+    /// Name the analyzer is registered under
+    fn name(&self) -> &str;
+
+    /// Analyse one part of a column
+    fn analyze_column(&self, _column: &TypedColumn, _profile: &mut ColumnProfile) {}
+
+    /// Analyse the whole dataset
+    fn analyze_dataset(&self, _dataset: &DatasetColumns, _profile: &mut Profile) {}
+}
+
+/// Analyzers by name, run in registration order
+#[derive(Clone)]
+pub struct AnalyzerRegistry {
+    analyzers: Vec<Arc<dyn Analyzer>>,
+}
+
+impl AnalyzerRegistry {
+    /// A registry without any analyzer
+    pub fn new() -> Self {
+        AnalyzerRegistry { analyzers: Vec::new() }
+    }
+
+    /// Add an analyzer, replacing the one registered under the same name
+    /// (which keeps its place in the order)
+    pub fn register(&mut self, analyzer: impl Analyzer + 'static) {
+        let analyzer: Arc<dyn Analyzer> = Arc::new(analyzer);
+        match self.analyzers.iter().position(|a| a.name() == analyzer.name()) {
+            Some(k) => self.analyzers[k] = analyzer,
+            None => self.analyzers.push(analyzer),
+        }
+    }
+
+    /// Remove the analyzer called `name`; false when there is none. The
+    /// [`STATISTICS`] analyzer cannot be removed, since every report reads the
+    /// statistics it computes (it can be replaced by registering another
+    /// analyzer under its name).
+    pub fn unregister(&mut self, name: &str) -> Result<bool, String> {
+        if name == STATISTICS {
+            return Err(format!("the '{}' analyzer cannot be unregistered", STATISTICS));
+        }
+        let before = self.analyzers.len();
+        self.analyzers.retain(|a| a.name() != name);
+        Ok(self.analyzers.len() != before)
+    }
+
+    /// The analyzers, in the order they run
+    pub fn analyzers(&self) -> Vec<Arc<dyn Analyzer>> {
+        self.analyzers.clone()
+    }
+
+    /// Names of the registered analyzers, in the order they run
+    pub fn names(&self) -> Vec<String> {
+        self.analyzers.iter().map(|a| a.name().to_string()).collect()
+    }
+}
+
+impl Default for AnalyzerRegistry {
+    /// The built-in analyzers
+    fn default() -> Self {
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(StatisticsAnalyzer);
+        registry.register(OutlierAnalyzer);
+        registry.register(CorrelationAnalyzer);
+        registry
+    }
+}
+
+/// Analyzers every profile is computed with
+static REGISTRY: LazyLock<RwLock<AnalyzerRegistry>> =
+    LazyLock::new(|| RwLock::new(AnalyzerRegistry::default()));
+
+/// Run an analyzer on every dataset profiled from now on, after the ones
+/// already registered; an analyzer of the same name (built-in ones included)
+/// is replaced
+pub fn register(analyzer: impl Analyzer + 'static) {
+    REGISTRY.write().unwrap_or_else(PoisonError::into_inner).register(analyzer);
+}
+
+/// Stop running the analyzer called `name`; false when there is none, an
+/// error for [`STATISTICS`]
+pub fn unregister(name: &str) -> Result<bool, String> {
+    REGISTRY.write().unwrap_or_else(PoisonError::into_inner).unregister(name)
+}
+
+/// The registered analyzers, in the order they run
+pub fn analyzers() -> Vec<Arc<dyn Analyzer>> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner).analyzers()
+}
+
+/// Names of the registered analyzers
+pub fn analyzer_names() -> Vec<String> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner).names()
+}
+
+/// Name of the analyzer computing the statistics every report is built on
+pub const STATISTICS: &str = "statistics";
+
+/// Descriptive statistics: min, max, mean (with its error bar for a random
+/// sample), median, quartiles and distinct values of numbers; value counts
+/// and mode of other values
+pub struct StatisticsAnalyzer;
+
+impl Analyzer for StatisticsAnalyzer {
+    fn name(&self) -> &str {
+        STATISTICS
+    }
+
+    fn analyze_column(&self, column: &TypedColumn, profile: &mut ColumnProfile) {
+        match column.values {
+            ColumnValues::Numeric(values) => profile.numeric = Some(numeric_stats(values, column.sample)),
+            ColumnValues::Text(values) => profile.categorical = Some(categorical_stats(values)),
+        }
+    }
+}
+
+/// Values outside 1.5 IQR of the quartiles, and the cleaning they call for
+pub struct OutlierAnalyzer;
+
+impl Analyzer for OutlierAnalyzer {
+    fn name(&self) -> &str {
+        "outliers"
+    }
+
+    fn analyze_column(&self, column: &TypedColumn, profile: &mut ColumnProfile) {
+        let ColumnValues::Numeric(values) = column.values else {
+            return;
+        };
+        if let Some(numeric) = profile.numeric.as_mut() {
+            let (q1, q3) = (numeric.q1, numeric.q3);
+            let (lower, upper) = (q1 - 1.5 * (q3 - q1), q3 + 1.5 * (q3 - q1));
+            numeric.outliers = values.iter().cloned().filter(|v| *v < lower || *v > upper).collect();
+        }
+        profile.suggestions.extend(
+            cleaning_suggestions(values)
+                .into_iter()
+                .filter(|s| s != "No cleaning needed"),
+        );
+    }
+}
+
+/// Pearson correlation of every pair of numeric columns
+pub struct CorrelationAnalyzer;
+
+impl Analyzer for CorrelationAnalyzer {
+    fn name(&self) -> &str {
+        "correlations"
+    }
+
+    fn analyze_dataset(&self, dataset: &DatasetColumns, profile: &mut Profile) {
+        profile.correlations = correlation_pairs(dataset.num_columns)
+            .into_iter()
+            .map(|(i, j, r)| Correlation {
+                a: dataset.headers[i].clone(),
+                b: dataset.headers[j].clone(),
+                r,
+            })
+            .collect();
+    }
+}
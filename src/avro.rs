@@ -0,0 +1,339 @@
+// Avro object container files: the embedded writer schema names the columns and
+// tells how every value is encoded. Blocks are decompressed by avro-schema
+// (null, deflate and snappy codecs); values are decoded here.
+use crate::formats::{ColumnType, Dataset, Numbers, decimal_text};
+use avro_schema::read::fallible_streaming_iterator::FallibleStreamingIterator;
+use avro_schema::schema::{
+    BytesLogical, FixedLogical, IntLogical, LongLogical, Record, Schema,
+};
+use serde_json::Value;
+use std::io::Read;
+
+/// Read every record of an Avro file; each field of the top-level record is a column
+pub fn read_avro<R: Read>(mut reader: R) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let metadata = avro_schema::read::read_metadata(&mut reader)
+        .map_err(|e| format!("Invalid Avro header: {}", e))?;
+    let record = metadata.record;
+    let headers = record.fields.iter().map(|f| f.name.clone()).collect();
+    let types = record.fields.iter().map(|f| column_type(&f.schema)).collect();
+    let mut numbers: Vec<Option<Numbers>> =
+        record.fields.iter().map(|f| numbers_for(&f.schema)).collect();
+    let mut rows = Vec::new();
+    let mut blocks =
+        avro_schema::read::block_iterator(reader, metadata.compression, metadata.marker);
+    while let Some(block) = blocks
+        .next()
+        .map_err(|e| format!("Invalid Avro block: {}", e))?
+    {
+        let mut data = block.data.as_slice();
+        for _ in 0..block.number_of_rows {
+            rows.push(read_row(&record, &mut numbers, &mut data)?);
+        }
+    }
+    Ok(Dataset {
+        headers,
+        rows,
+        sample: None,
+        types: Some(types),
+        numbers: Some(numbers),
+    })
+}
+
+/// Column type of a field schema; a union takes the type its non-null
+/// branches share, if any
+fn column_type(schema: &Schema) -> ColumnType {
+    match schema {
+        Schema::Boolean => ColumnType::Boolean,
+        Schema::Int(Some(IntLogical::Date)) => ColumnType::Date,
+        Schema::Int(None) | Schema::Long(None) | Schema::Float | Schema::Double => ColumnType::Numeric,
+        Schema::Bytes(Some(BytesLogical::Decimal(..))) => ColumnType::Numeric,
+        Schema::Fixed(fixed) if matches!(fixed.logical, Some(FixedLogical::Decimal(..))) => {
+            ColumnType::Numeric
+        }
+        Schema::Union(branches) => {
+            let mut types = branches
+                .iter()
+                .filter(|b| !matches!(b, Schema::Null))
+                .map(column_type);
+            match types.next() {
+                Some(first) if types.all(|t| t == first) => first,
+                _ => ColumnType::Text,
+            }
+        }
+        _ => ColumnType::Text,
+    }
+}
+
+/// An empty store for a field read as numbers: ints, longs, floats, doubles
+/// and decimals, alone or in a union with null; `None` for the fields written
+/// as text
+fn numbers_for(schema: &Schema) -> Option<Numbers> {
+    match schema {
+        Schema::Int(None) | Schema::Long(None) => Some(Numbers::Int(Vec::new())),
+        Schema::Float => Some(Numbers::Float32(Vec::new())),
+        Schema::Double => Some(Numbers::Float(Vec::new())),
+        Schema::Bytes(Some(BytesLogical::Decimal(_, scale))) => {
+            Some(Numbers::Decimal(Vec::new(), i32::try_from(*scale).ok()?))
+        }
+        Schema::Fixed(fixed) => match fixed.logical {
+            Some(FixedLogical::Decimal(_, scale)) => {
+                Some(Numbers::Decimal(Vec::new(), i32::try_from(scale).ok()?))
+            }
+            _ => None,
+        },
+        Schema::Union(branches) => {
+            let mut stores = branches
+                .iter()
+                .filter(|b| !matches!(b, Schema::Null))
+                .map(numbers_for);
+            let first = stores.next()??;
+            stores.all(|s| s.as_ref() == Some(&first)).then_some(first)
+        }
+        _ => None,
+    }
+}
+
+/// One record as cells, in the text form the analysis types columns from:
+/// nulls are missing, strings and enum symbols stay as they are, dates are
+/// `YYYY-MM-DD` and nested values are written as JSON. Fields with a store in
+/// `numbers` are decoded into it and left empty.
+fn read_row(
+    record: &Record,
+    numbers: &mut [Option<Numbers>],
+    data: &mut &[u8],
+) -> Result<Vec<String>, String> {
+    record
+        .fields
+        .iter()
+        .zip(numbers.iter_mut())
+        .map(|(field, numbers)| {
+            if let Some(numbers) = numbers {
+                push_number(numbers, &field.schema, data)?;
+                return Ok(String::new());
+            }
+            Ok(match decode(&field.schema, data)? {
+                Value::Null => String::new(),
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Decode a value of a field [`numbers_for`] made a store for
+fn push_number(numbers: &mut Numbers, schema: &Schema, data: &mut &[u8]) -> Result<(), String> {
+    match (schema, numbers) {
+        (Schema::Union(branches), numbers) => {
+            return push_number(numbers, union_branch(branches, data)?, data);
+        }
+        (Schema::Null, numbers) => numbers.push_null(),
+        (Schema::Int(_) | Schema::Long(_), Numbers::Int(v)) => v.push(Some(read_long(data)?)),
+        (Schema::Float, Numbers::Float32(v)) => v.push(Some(read_float(data)?)),
+        (Schema::Double, Numbers::Float(v)) => v.push(Some(read_double(data)?)),
+        (Schema::Bytes(_), Numbers::Decimal(v, _)) => {
+            let len = read_len(data)?;
+            v.push(Some(unscaled(take(data, len)?)?));
+        }
+        (Schema::Fixed(fixed), Numbers::Decimal(v, _)) => {
+            v.push(Some(unscaled(take(data, fixed.size)?)?));
+        }
+        _ => unreachable!("the store was made for this schema"),
+    }
+    Ok(())
+}
+
+fn decode(schema: &Schema, data: &mut &[u8]) -> Result<Value, String> {
+    Ok(match schema {
+        Schema::Null => Value::Null,
+        Schema::Boolean => Value::Bool(take(data, 1)?[0] != 0),
+        Schema::Int(None) | Schema::Long(None) => Value::from(read_long(data)?),
+        Schema::Int(Some(IntLogical::Date)) => {
+            let days = read_long(data)?;
+            let date = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+                .and_then(|epoch| epoch.checked_add_signed(chrono::Duration::days(days)))
+                .ok_or("Avro date out of range")?;
+            Value::String(date.format("%Y-%m-%d").to_string())
+        }
+        Schema::Int(Some(IntLogical::Time)) => time_of_day(read_long(data)? * 1_000)?,
+        Schema::Long(Some(LongLogical::Time)) => time_of_day(read_long(data)?)?,
+        Schema::Long(Some(LongLogical::TimestampMillis | LongLogical::LocalTimestampMillis)) => {
+            timestamp(chrono::DateTime::from_timestamp_millis(read_long(data)?))?
+        }
+        Schema::Long(Some(LongLogical::TimestampMicros | LongLogical::LocalTimestampMicros)) => {
+            timestamp(chrono::DateTime::from_timestamp_micros(read_long(data)?))?
+        }
+        Schema::Float => number(f64::from(read_float(data)?)),
+        Schema::Double => number(read_double(data)?),
+        Schema::Bytes(logical) => {
+            let len = read_len(data)?;
+            let bytes = take(data, len)?;
+            match logical {
+                Some(BytesLogical::Decimal(_, scale)) => Value::String(decimal(bytes, *scale)?),
+                None => Value::String(hex(bytes)),
+            }
+        }
+        Schema::String(_) => {
+            let len = read_len(data)?;
+            Value::String(String::from_utf8_lossy(take(data, len)?).into_owned())
+        }
+        Schema::Record(record) => {
+            let mut object = serde_json::Map::new();
+            for field in &record.fields {
+                object.insert(field.name.clone(), decode(&field.schema, data)?);
+            }
+            Value::Object(object)
+        }
+        Schema::Enum(e) => {
+            let index = read_long(data)?;
+            let symbol = usize::try_from(index)
+                .ok()
+                .and_then(|i| e.symbols.get(i))
+                .ok_or_else(|| format!("Invalid symbol {} for Avro enum '{}'", index, e.name))?;
+            Value::String(symbol.clone())
+        }
+        Schema::Array(items) => {
+            let mut values = Vec::new();
+            read_blocks(data, |data| {
+                values.push(decode(items, data)?);
+                Ok(())
+            })?;
+            Value::Array(values)
+        }
+        Schema::Map(values) => {
+            let mut object = serde_json::Map::new();
+            read_blocks(data, |data| {
+                let len = read_len(data)?;
+                let key = String::from_utf8_lossy(take(data, len)?).into_owned();
+                object.insert(key, decode(values, data)?);
+                Ok(())
+            })?;
+            Value::Object(object)
+        }
+        Schema::Union(branches) => decode(union_branch(branches, data)?, data)?,
+        Schema::Fixed(fixed) => {
+            let bytes = take(data, fixed.size)?;
+            match fixed.logical {
+                Some(FixedLogical::Decimal(_, scale)) => Value::String(decimal(bytes, scale)?),
+                Some(FixedLogical::Duration) if bytes.len() == 12 => {
+                    let part = |i: usize| {
+                        u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+                    };
+                    Value::String(format!(
+                        "P{}M{}DT{}S",
+                        part(0),
+                        part(4),
+                        f64::from(part(8)) / 1000.0
+                    ))
+                }
+                _ => Value::String(hex(bytes)),
+            }
+        }
+    })
+}
+
+/// Items of an array or map: blocks of `count` items (preceded by their size
+/// in bytes when `count` is negative), ended by an empty block
+fn read_blocks(
+    data: &mut &[u8],
+    mut item: impl FnMut(&mut &[u8]) -> Result<(), String>,
+) -> Result<(), String> {
+    loop {
+        let count = read_long(data)?;
+        if count == 0 {
+            return Ok(());
+        }
+        if count < 0 {
+            read_long(data)?;
+        }
+        for _ in 0..count.unsigned_abs() {
+            item(data)?;
+        }
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if data.len() < len {
+        return Err("Truncated Avro record".to_string());
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Ok(head)
+}
+
+/// A zigzag-encoded variable-length integer
+fn read_long(data: &mut &[u8]) -> Result<i64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = take(data, 1)?[0];
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+    }
+    Err("Invalid Avro integer".to_string())
+}
+
+fn read_float(data: &mut &[u8]) -> Result<f32, String> {
+    let bytes = take(data, 4)?;
+    Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_double(data: &mut &[u8]) -> Result<f64, String> {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(take(data, 8)?);
+    Ok(f64::from_le_bytes(bytes))
+}
+
+/// The branch a union value was written with
+fn union_branch<'a>(branches: &'a [Schema], data: &mut &[u8]) -> Result<&'a Schema, String> {
+    let index = read_long(data)?;
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| branches.get(i))
+        .ok_or_else(|| format!("Invalid Avro union branch {}", index))
+}
+
+fn read_len(data: &mut &[u8]) -> Result<usize, String> {
+    usize::try_from(read_long(data)?).map_err(|_| "Negative Avro length".to_string())
+}
+
+fn number(value: f64) -> Value {
+    serde_json::Number::from_f64(value).map_or_else(|| Value::String(value.to_string()), Value::Number)
+}
+
+fn time_of_day(micros: i64) -> Result<Value, String> {
+    let time = u32::try_from(micros.div_euclid(1_000_000))
+        .ok()
+        .and_then(|secs| {
+            let nanos = (micros.rem_euclid(1_000_000) * 1_000) as u32;
+            chrono::NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos)
+        })
+        .ok_or("Avro time out of range")?;
+    Ok(Value::String(time.format("%H:%M:%S%.f").to_string()))
+}
+
+fn timestamp(value: Option<chrono::DateTime<chrono::Utc>>) -> Result<Value, String> {
+    let value = value.ok_or("Avro timestamp out of range")?;
+    Ok(Value::String(value.format("%Y-%m-%d %H:%M:%S%.f").to_string()))
+}
+
+/// A big-endian two's-complement unscaled integer with `scale` decimal places
+fn decimal(bytes: &[u8], scale: usize) -> Result<String, String> {
+    let scale = i32::try_from(scale).map_err(|_| "Avro decimal scale out of range")?;
+    Ok(decimal_text(unscaled(bytes)?, scale))
+}
+
+/// The unscaled value of a decimal, a big-endian two's-complement integer
+fn unscaled(bytes: &[u8]) -> Result<i128, String> {
+    if bytes.len() > 16 {
+        return Err("Avro decimal wider than 128 bits".to_string());
+    }
+    let fill = if bytes.first().is_some_and(|b| b & 0x80 != 0) { 0xff } else { 0 };
+    let mut buf = [fill; 16];
+    buf[16 - bytes.len()..].copy_from_slice(bytes);
+    Ok(i128::from_be_bytes(buf))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
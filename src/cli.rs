@@ -0,0 +1,467 @@
+// Command-line interface: subcommands, their options and the process exit codes
+use crate::formats::{CsvOptions, ReadOptions, parse_encoding};
+use crate::report::{ChartFormat, ChartOptions};
+use crate::sample::{SampleMethod, Sampling};
+use crate::sniff::InputFormat;
+use encoding_rs::Encoding;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// Everything went fine
+pub const EXIT_OK: u8 = 0;
+/// The command ran but the data failed its checks (`validate`)
+pub const EXIT_CHECKS_FAILED: u8 = 1;
+/// Invalid command line; also what clap exits with on a parse error
+pub const EXIT_USAGE: u8 = 2;
+/// An input could not be read or a report could not be written
+pub const EXIT_ERROR: u8 = 3;
+
+const SUBCOMMANDS: &[&str] = &[
+    "report", "profile", "validate", "diff", "drift", "clean", "help",
+];
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "datastory",
+    version,
+    about = "Automated data storytelling: profile, report on, validate and compare datasets",
+    after_help = "Exit codes: 0 success, 1 validation checks failed, 2 invalid usage, 3 input or output error.\n\
+                  `datastory <data_file> [output_report]` is short for `datastory report <data_file> [output_report]`."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    /// Print details about what was read and written
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+    /// Only print errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// Worker threads for column statistics, charts and correlations
+    /// [default: one per CPU]
+    #[arg(short = 'j', long, global = true, value_name = "N", value_parser = parse_threads)]
+    pub threads: Option<usize>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Generate a report of a data file in any registered format (see --format)
+    Report(ReportArgs),
+    /// Write the machine-readable JSON profile of a data file
+    Profile(ProfileArgs),
+    /// Check a data file against a TOML or YAML rules file
+    Validate(ValidateArgs),
+    /// Compare the profiles of two versions of a dataset
+    Diff(DiffArgs),
+    /// Measure distribution drift between a reference and a current dataset
+    Drift(DriftArgs),
+    /// Print cleaning suggestions for every column
+    Clean(CleanArgs),
+}
+
+/// Report format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Html,
+    #[value(alias = "markdown")]
+    Md,
+    Json,
+}
+
+impl Format {
+    /// Name understood by the report generators
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Md => "md",
+            Format::Json => "json",
+        }
+    }
+
+    /// Output file name used when none is given, e.g. `rapport.html`
+    pub fn default_file_name(self, stem: &str) -> String {
+        format!("{}.{}", stem, self.as_str())
+    }
+}
+
+/// How data files are read and which part of them is analysed
+#[derive(Debug, Clone, Args)]
+pub struct InputArgs {
+    /// Input format, instead of detecting it from the file content
+    #[arg(long, value_name = "csv|parquet|json|ndjson|xlsx|arrow|avro", value_parser = parse_input_format)]
+    pub input_format: Option<InputFormat>,
+    /// Field delimiter for CSV files: a single character, or `\t` for tabs [default: detected]
+    #[arg(short, long, value_name = "CHAR", value_parser = parse_char)]
+    pub delimiter: Option<u8>,
+    /// Quote character for CSV files [default: detected]
+    #[arg(long, value_name = "CHAR", value_parser = parse_char)]
+    pub quote: Option<u8>,
+    /// Escape character inside quoted CSV fields [default: quotes are doubled]
+    #[arg(long, value_name = "CHAR", value_parser = parse_char)]
+    pub escape: Option<u8>,
+    /// The first CSV row holds the column names [default: detected]
+    #[arg(long, conflicts_with = "no_header")]
+    pub header: bool,
+    /// The first CSV row is data; columns are named column_1, column_2, ...
+    #[arg(long)]
+    pub no_header: bool,
+    /// Lines to skip at the start of CSV files, before the header
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub skip_rows: usize,
+    /// Ignore CSV lines starting with this character, e.g. `#`
+    #[arg(long, value_name = "CHAR", value_parser = parse_char)]
+    pub comment: Option<u8>,
+    /// Accept CSV rows with fewer or more fields than the header
+    #[arg(long)]
+    pub flexible: bool,
+    /// Encoding of CSV files, e.g. utf-8, latin1, windows-1252, utf-16le [default: detected]
+    #[arg(long, value_name = "LABEL", value_parser = parse_encoding)]
+    pub encoding: Option<&'static Encoding>,
+    /// Sheet of a workbook to read, by name or 1-based position [default: all sheets
+    /// for `report` in html or md (the other report formats need it for a workbook
+    /// of several sheets), the first sheet otherwise]
+    #[arg(long, value_name = "NAME|N")]
+    pub sheet: Option<String>,
+    /// Only analyse these columns (repeat the option or separate names with commas)
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub include: Vec<String>,
+    /// Leave these columns out of the analysis
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+    /// Only analyse a sample of N rows of each file (the first N unless
+    /// --sample-method says otherwise)
+    #[arg(long, value_name = "N", conflicts_with = "sample_fraction")]
+    pub sample: Option<usize>,
+    /// Only analyse this share of the rows of each file, e.g. 0.1
+    #[arg(long, value_name = "F")]
+    pub sample_fraction: Option<f64>,
+    /// How sampled rows are chosen: the first rows, a reservoir sample drawn
+    /// while the file streams by, a sample stratified by --stratify-by, or a
+    /// random fraction [default: stratified with --stratify-by, fraction with
+    /// --sample-fraction, head otherwise]
+    #[arg(long, value_name = "head|reservoir|stratified|fraction", value_parser = parse_sample_method)]
+    pub sample_method: Option<SampleMethod>,
+    /// Categorical column whose values are sampled in proportion to their frequency
+    #[arg(long, value_name = "COLUMN")]
+    pub stratify_by: Option<String>,
+    /// Seed of random samples [default: 42]
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+}
+
+/// Where reports are written
+#[derive(Debug, Clone, Args)]
+pub struct OutputArgs {
+    /// Directory the report is written into; created if missing
+    #[arg(short = 'o', long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+}
+
+/// Chart rendering options shared by the reports that contain charts
+#[derive(Debug, Clone, Args)]
+pub struct ChartArgs {
+    /// Leave charts out of the report
+    #[arg(long)]
+    pub no_charts: bool,
+    /// Embed charts (as inline SVG) and the stylesheet in the HTML file
+    #[arg(long)]
+    pub self_contained: bool,
+    /// Directory for chart images, relative to the report [default: assets]
+    #[arg(long, value_name = "DIR")]
+    pub assets_dir: Option<String>,
+    /// Image format of chart files: png, or svg (sharp at any zoom) [default: png]
+    #[arg(long, value_name = "png|svg", value_parser = parse_chart_format)]
+    pub chart_format: Option<ChartFormat>,
+    /// Chart width, in pixels at 96 DPI
+    #[arg(long, value_name = "PX", default_value_t = 640, value_parser = clap::value_parser!(u32).range(1..))]
+    pub chart_width: u32,
+    /// Chart height, in pixels at 96 DPI
+    #[arg(long, value_name = "PX", default_value_t = 480, value_parser = clap::value_parser!(u32).range(1..))]
+    pub chart_height: u32,
+    /// Resolution of PNG charts; 192 doubles their pixel size for print or
+    /// high-density screens [default: 96]
+    #[arg(long, value_name = "DPI", value_parser = clap::value_parser!(u32).range(1..))]
+    pub chart_dpi: Option<u32>,
+}
+
+impl ChartArgs {
+    /// Chart image options for these arguments
+    pub fn chart_options(&self) -> ChartOptions {
+        let defaults = ChartOptions::default();
+        ChartOptions {
+            format: self.chart_format.unwrap_or(defaults.format),
+            width: self.chart_width,
+            height: self.chart_height,
+            dpi: self.chart_dpi.unwrap_or(defaults.dpi),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// CSV, Parquet, JSON, NDJSON, spreadsheet, Arrow or Avro file to analyse; `-` reads stdin
+    pub input: String,
+    /// Report file name, `-` for stdout [default: rapport.<format>; stdout for
+    /// a Markdown or JSON report on stdin]
+    pub output: Option<String>,
+    /// Report format: html, interactive, md, json, pdf or another registered
+    /// renderer; `json` writes the same document as `profile`
+    #[arg(short, long, value_name = "FORMAT", default_value = "html", value_parser = parse_report_format)]
+    pub format: String,
+    /// Render the report from this MiniJinja template instead of the built-in one
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<String>,
+    /// Interactive offline HTML report with sortable tables and zoomable charts
+    #[arg(long)]
+    pub interactive: bool,
+    #[command(flatten)]
+    pub charts: ChartArgs,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+    #[command(flatten)]
+    pub output_args: OutputArgs,
+}
+
+impl ReportArgs {
+    /// Name of the renderer to use: `--interactive` turns `html` into `interactive`
+    pub fn renderer_name(&self) -> &str {
+        match self.format.as_str() {
+            "html" if self.interactive => "interactive",
+            name => name,
+        }
+    }
+
+    /// Refuse chart options the renderer would ignore: interactive reports
+    /// draw every chart as inline SVG and write no chart files
+    pub fn check_chart_options(&self) -> Result<(), String> {
+        if self.renderer_name() != "interactive" {
+            return Ok(());
+        }
+        let charts = &self.charts;
+        if charts.chart_format == Some(ChartFormat::Png) {
+            return Err("--chart-format png does not apply to interactive reports, whose charts are inline SVG".to_string());
+        }
+        if charts.chart_dpi.is_some() {
+            return Err("--chart-dpi only applies to PNG charts; interactive reports draw them as inline SVG".to_string());
+        }
+        if charts.assets_dir.is_some() {
+            return Err("--assets-dir does not apply to interactive reports, which keep their charts inline".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ProfileArgs {
+    /// CSV, Parquet, JSON, NDJSON, spreadsheet, Arrow or Avro file to profile; `-` reads stdin
+    pub input: String,
+    /// Profile file name, `-` for stdout [default: profile.json; stdout for stdin]
+    pub output: Option<String>,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+    #[command(flatten)]
+    pub output_args: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Data file to check; `-` reads stdin
+    pub input: String,
+    /// Rules file (`.toml`, `.yaml` or `.yml`)
+    pub rules: String,
+    /// Report file name, `-` for stdout [default: validation.<format>]
+    pub output: Option<String>,
+    /// Report format
+    #[arg(short, long, value_enum, default_value_t = Format::Html)]
+    pub format: Format,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+    #[command(flatten)]
+    pub output_args: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Previous version of the dataset
+    pub old: String,
+    /// New version of the dataset
+    pub new: String,
+    /// Report file name, `-` for stdout [default: diff.<format>]
+    pub output: Option<String>,
+    /// Report format
+    #[arg(short, long, value_enum, default_value_t = Format::Html)]
+    pub format: Format,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+    #[command(flatten)]
+    pub output_args: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DriftArgs {
+    /// Reference dataset
+    pub reference: String,
+    /// Current dataset
+    pub current: String,
+    /// Report file name, `-` for stdout [default: drift.<format>]
+    pub output: Option<String>,
+    /// Report format
+    #[arg(short, long, value_enum, default_value_t = Format::Html)]
+    pub format: Format,
+    #[command(flatten)]
+    pub charts: ChartArgs,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+    #[command(flatten)]
+    pub output_args: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct CleanArgs {
+    /// Data file to inspect; `-` reads stdin
+    pub input: String,
+    /// Print the suggestions as a JSON object keyed by column name
+    #[arg(long)]
+    pub json: bool,
+    #[command(flatten)]
+    pub input_args: InputArgs,
+}
+
+/// A report format must name a registered renderer; aliases such as
+/// `markdown` become the renderer's own name
+fn parse_report_format(value: &str) -> Result<String, String> {
+    match crate::renderer::renderer(value) {
+        Some(renderer) => Ok(renderer.name().to_string()),
+        None => Err(format!(
+            "unknown report format '{}' (expected one of: {})",
+            value,
+            crate::renderer::renderer_names().join(", ")
+        )),
+    }
+}
+
+fn parse_input_format(value: &str) -> Result<InputFormat, String> {
+    value.parse()
+}
+
+fn parse_sample_method(value: &str) -> Result<SampleMethod, String> {
+    value.parse()
+}
+
+fn parse_chart_format(value: &str) -> Result<ChartFormat, String> {
+    value.parse()
+}
+
+impl InputArgs {
+    /// Reader options for these arguments
+    pub fn read_options(&self) -> ReadOptions {
+        let has_headers = match (self.header, self.no_header) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+        ReadOptions {
+            format: self.input_format,
+            csv: CsvOptions {
+                delimiter: self.delimiter,
+                quote: self.quote,
+                escape: self.escape,
+                has_headers,
+                skip_rows: self.skip_rows,
+                comment: self.comment,
+                flexible: self.flexible,
+                encoding: self.encoding,
+            },
+            sheet: self.sheet.clone(),
+            sample: self.sampling().ok().flatten(),
+        }
+    }
+
+    /// The row sample these arguments ask for; see [`Sampling::new`]
+    pub fn sampling(&self) -> Result<Option<Sampling>, String> {
+        Sampling::new(
+            self.sample_method,
+            self.sample,
+            self.sample_fraction,
+            self.stratify_by.clone(),
+            self.seed,
+        )
+    }
+}
+
+impl Command {
+    /// Input options of the subcommand, checked before anything is read
+    pub fn input_args(&self) -> &InputArgs {
+        match self {
+            Command::Report(args) => &args.input_args,
+            Command::Profile(args) => &args.input_args,
+            Command::Validate(args) => &args.input_args,
+            Command::Diff(args) => &args.input_args,
+            Command::Drift(args) => &args.input_args,
+            Command::Clean(args) => &args.input_args,
+        }
+    }
+}
+
+fn parse_threads(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("expected a positive number of threads, got '{}'", value)),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// A single ASCII character; `\t` (or `tab`) for a tab
+fn parse_char(value: &str) -> Result<u8, String> {
+    match value {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ if value.len() == 1 => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "expected a single ASCII character or `\\t`, got '{}'",
+            value
+        )),
+    }
+}
+
+/// Accept the historical `datastory <data_file> [output]` form by inserting
+/// the `report` subcommand when the first argument after the global options
+/// (`-v`, `-q`, `-j N`) is not a subcommand or option; `-` (standard input)
+/// is a data file
+pub fn normalize_args(mut args: Vec<String>) -> Vec<String> {
+    let mut i = 1;
+    while let Some(skip) = args.get(i).and_then(|arg| global_option_len(arg)) {
+        i += skip;
+    }
+    if let Some(first) = args.get(i)
+        && (first == "-" || !first.starts_with('-'))
+        && !SUBCOMMANDS.contains(&first.as_str())
+    {
+        args.insert(i, "report".to_string());
+    }
+    args
+}
+
+/// How many arguments the global option starting at `arg` takes up (two for
+/// `-j N`), or `None` when `arg` is not a global option. Short flags may be
+/// bundled, e.g. `-vj4` or `-vj 4`.
+fn global_option_len(arg: &str) -> Option<usize> {
+    match arg {
+        "-v" | "--verbose" | "-q" | "--quiet" => return Some(1),
+        "--threads" => return Some(2),
+        a if a.starts_with("--threads=") => return Some(1),
+        _ => {}
+    }
+    let flags = arg.strip_prefix('-').filter(|f| !f.is_empty() && !f.starts_with('-'))?;
+    let (switches, threads) = match flags.split_once('j') {
+        Some((switches, value)) => (switches, Some(value)),
+        None => (flags, None),
+    };
+    if !switches.chars().all(|c| c == 'v' || c == 'q') {
+        return None;
+    }
+    match threads {
+        None => Some(1),
+        Some("") => Some(2),
+        Some(value) if value.bytes().all(|b| b.is_ascii_digit()) => Some(1),
+        Some(_) => None,
+    }
+}
@@ -0,0 +1,91 @@
+// Transparent decompression of gzip, zstd, bzip2 and xz input files. Readers
+// stream through the decompressor; nothing is written to disk. The path `-`
+// stands for standard input.
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::OnceLock;
+
+/// Input path standing for standard input
+pub const STDIN: &str = "-";
+
+static STDIN_DATA: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Compression wrapping an input file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Compression recognised from the first bytes of a file
+    pub fn from_magic(prefix: &[u8]) -> Option<Compression> {
+        if prefix.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if prefix.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if prefix.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if prefix.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Compression named by the last extension of a path, e.g. `data.csv.gz`
+    pub fn from_extension(path: &str) -> Option<Compression> {
+        let ext = std::path::Path::new(path).extension()?.to_str()?;
+        match ext.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" | "bzip2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    /// Compression of a file: magic bytes first, then the extension
+    pub fn detect(path: &str) -> std::io::Result<Compression> {
+        let mut magic = Vec::with_capacity(6);
+        open_raw(path)?.take(6).read_to_end(&mut magic)?;
+        Ok(Compression::from_magic(&magic)
+            .or_else(|| Compression::from_extension(path))
+            .unwrap_or(Compression::None))
+    }
+}
+
+/// Standard input, read in full on first use: the format is sniffed from its
+/// first bytes before it is read, and `diff` or `drift` may read it twice
+fn stdin_data() -> std::io::Result<&'static [u8]> {
+    if let Some(data) = STDIN_DATA.get() {
+        return Ok(data);
+    }
+    let mut data = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut data)?;
+    Ok(STDIN_DATA.get_or_init(|| data))
+}
+
+/// The bytes of a file or of standard input, as they are stored
+fn open_raw(path: &str) -> std::io::Result<Box<dyn BufRead>> {
+    if path == STDIN {
+        Ok(Box::new(stdin_data()?))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Open a file for reading, decompressing it on the fly when it is compressed
+pub fn open(path: &str) -> std::io::Result<Box<dyn Read>> {
+    let file = open_raw(path)?;
+    Ok(match Compression::detect(path)? {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(file)?),
+        Compression::Bzip2 => Box::new(bzip2::bufread::MultiBzDecoder::new(file)),
+        Compression::Xz => Box::new(liblzma::bufread::XzDecoder::new_multi_decoder(file)),
+    })
+}
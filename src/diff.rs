@@ -0,0 +1,313 @@
+// Profile diffing: what changed between two versions of a dataset
+// (schema, row count, missing rates, statistics and top categories).
+use crate::analyze::{ColumnProfile, Profile, ValueCount, infer_column_type, is_missing, profile};
+use crate::error::DataError;
+use crate::formats::{Dataset, read_dataset};
+use crate::templates::{TemplateKind, render};
+use serde::Serialize;
+use std::io::Write;
+
+/// Default HTML diff report template
+pub const DEFAULT_DIFF_HTML_TEMPLATE: &str = include_str!("../templates/diff.html");
+/// Default Markdown diff report template
+pub const DEFAULT_DIFF_MARKDOWN_TEMPLATE: &str = include_str!("../templates/diff.md");
+
+/// Number of most frequent values compared for categorical columns
+const TOP_CATEGORIES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileDiff {
+    pub old_source: String,
+    pub new_source: String,
+    pub old_rows: usize,
+    pub new_rows: usize,
+    pub added_columns: Vec<ColumnSchema>,
+    pub removed_columns: Vec<ColumnSchema>,
+    pub retyped_columns: Vec<TypeChange>,
+    /// Columns present in both versions, in the new file's order
+    pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub col_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeChange {
+    pub name: String,
+    pub old_type: String,
+    pub new_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnDiff {
+    pub name: String,
+    pub old_type: String,
+    pub new_type: String,
+    pub old_missing_rate: f64,
+    pub new_missing_rate: f64,
+    /// Statistic shifts, only when the column is numeric in both versions
+    pub stats: Vec<StatShift>,
+    /// Top category changes, only when the column is non-numeric in both versions
+    pub categories: Option<CategoryShift>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatShift {
+    pub stat: String,
+    pub old: f64,
+    pub new: f64,
+    pub delta: f64,
+    /// `delta` relative to the old value, `None` when the old value is 0
+    pub relative: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryShift {
+    pub old_top: Vec<ValueCount>,
+    pub new_top: Vec<ValueCount>,
+    /// Values that entered the top categories
+    pub entered: Vec<String>,
+    /// Values that dropped out of the top categories
+    pub left: Vec<String>,
+}
+
+/// Profile both files and compare them
+pub fn diff_files(old_path: &str, new_path: &str) -> Result<ProfileDiff, Box<dyn std::error::Error>> {
+    let old = read_dataset(old_path)?;
+    let new = read_dataset(new_path)?;
+    Ok(diff_datasets(&old, &new, old_path, new_path))
+}
+
+/// Profile two versions of a dataset and compare them; see [`diff_profiles`]
+pub fn diff_datasets(old: &Dataset, new: &Dataset, old_source: &str, new_source: &str) -> ProfileDiff {
+    diff_profiles(
+        &profile(old).with_source(old_source),
+        &profile(new).with_source(new_source),
+    )
+}
+
+/// Compare the profiles of two versions of a dataset, matching columns by
+/// name. Top categories are those of the non-numeric values of a column.
+pub fn diff_profiles(old: &Profile, new: &Profile) -> ProfileDiff {
+    let old_columns = profile_columns(old);
+    let new_columns = profile_columns(new);
+    let mut diff = ProfileDiff {
+        old_source: old.source.clone(),
+        new_source: new.source.clone(),
+        old_rows: old.row_count,
+        new_rows: new.row_count,
+        added_columns: Vec::new(),
+        removed_columns: Vec::new(),
+        retyped_columns: Vec::new(),
+        columns: Vec::new(),
+    };
+    for column in &old_columns {
+        if !new_columns.iter().any(|c| c.name == column.name) {
+            diff.removed_columns.push(ColumnSchema {
+                name: column.name.to_string(),
+                col_type: column.col_type().to_string(),
+            });
+        }
+    }
+    for new_column in &new_columns {
+        let name = new_column.name.to_string();
+        let new_type = new_column.col_type();
+        let Some(old_column) = old_columns.iter().find(|c| c.name == new_column.name) else {
+            diff.added_columns.push(ColumnSchema {
+                name,
+                col_type: new_type.to_string(),
+            });
+            continue;
+        };
+        let old_type = old_column.col_type();
+        if old_type != new_type {
+            diff.retyped_columns.push(TypeChange {
+                name: name.clone(),
+                old_type: old_type.to_string(),
+                new_type: new_type.to_string(),
+            });
+        }
+        let stats = match (old_column.numeric, new_column.numeric) {
+            (Some(old), Some(new)) if old_type == "numeric" && new_type == "numeric" => stat_shifts(old, new),
+            _ => Vec::new(),
+        };
+        let categories = if old_type != "numeric" && new_type != "numeric" {
+            Some(category_shift(old_column.top_categories(), new_column.top_categories()))
+        } else {
+            None
+        };
+        diff.columns.push(ColumnDiff {
+            name,
+            old_type: old_type.to_string(),
+            new_type: new_type.to_string(),
+            old_missing_rate: old_column.missing_rate(old.row_count),
+            new_missing_rate: new_column.missing_rate(new.row_count),
+            stats,
+            categories,
+        });
+    }
+    diff
+}
+
+/// Write the diff as an HTML, Markdown (`md`) or JSON report
+pub fn generate_diff_report(
+    diff: &ProfileDiff,
+    report_name: &str,
+    format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = match format {
+        "html" => render(DEFAULT_DIFF_HTML_TEMPLATE, TemplateKind::Html, diff)?,
+        "md" | "markdown" => render(DEFAULT_DIFF_MARKDOWN_TEMPLATE, TemplateKind::Markdown, diff)?,
+        "json" => serde_json::to_string_pretty(diff)? + "\n",
+        other => {
+            return Err(DataError::UnsupportedFormat(format!("Unsupported diff format '{}'", other)).into());
+        }
+    };
+    let mut file = crate::report::create_report_file(report_name)?;
+    file.write_all(output.as_bytes())?;
+    Ok(())
+}
+
+/// A column of a profile, put back together from its numeric and
+/// non-numeric parts
+struct ProfiledColumn<'a> {
+    name: &'a str,
+    numeric: Option<&'a ColumnProfile>,
+    text: Option<&'a ColumnProfile>,
+}
+
+/// The columns of a profile in dataset order; columns without any value
+/// have no part to be found from
+fn profile_columns(profile: &Profile) -> Vec<ProfiledColumn<'_>> {
+    let mut parts: Vec<&ColumnProfile> = profile.columns.iter().collect();
+    parts.sort_by_key(|c| c.index);
+    let mut columns: Vec<ProfiledColumn> = Vec::new();
+    let mut last_index = None;
+    for part in parts {
+        if last_index != Some(part.index) {
+            columns.push(ProfiledColumn {
+                name: &part.name,
+                numeric: None,
+                text: None,
+            });
+            last_index = Some(part.index);
+        }
+        let column = columns.last_mut().expect("a column was just pushed");
+        if part.numeric.is_some() {
+            column.numeric = Some(part);
+        } else {
+            column.text = Some(part);
+        }
+    }
+    columns
+}
+
+impl<'a> ProfiledColumn<'a> {
+    /// Value counts of the non-numeric cells, most frequent first
+    fn frequencies(&self) -> &[ValueCount] {
+        self.text
+            .and_then(|c| c.categorical.as_ref())
+            .map_or(&[], |c| c.frequencies.as_slice())
+    }
+
+    /// Type of the whole column ignoring missing cells: the profiled type of a
+    /// column with only non-numeric values, unless its missing cells hid a
+    /// boolean or date column from the profile
+    fn col_type(&self) -> &'a str {
+        let present: Vec<String> = self
+            .frequencies()
+            .iter()
+            .filter(|v| !is_missing(&v.value))
+            .map(|v| v.value.clone())
+            .collect();
+        match (self.numeric.is_some(), self.text) {
+            (true, _) if present.is_empty() => "numeric",
+            (true, _) => "categorical",
+            (false, Some(text)) if text.col_type == "categorical" => {
+                match infer_column_type(&present) {
+                    inferred @ ("boolean" | "date" | "empty") => inferred,
+                    _ => "categorical",
+                }
+            }
+            (false, Some(text)) => &text.col_type,
+            (false, None) => "empty",
+        }
+    }
+
+    /// Share of the rows with no value in this column; rows are missing when
+    /// they are in neither part, as the nulls of a schema-typed numeric column
+    fn missing_rate(&self, row_count: usize) -> f64 {
+        if row_count == 0 {
+            return 0.0;
+        }
+        let numeric = self.numeric.map_or(0, |c| c.count);
+        let present: usize = self
+            .frequencies()
+            .iter()
+            .filter(|v| !is_missing(&v.value))
+            .map(|v| v.count)
+            .sum();
+        row_count.saturating_sub(numeric + present) as f64 / row_count as f64
+    }
+
+    fn top_categories(&self) -> Vec<ValueCount> {
+        self.frequencies()
+            .iter()
+            .filter(|v| !is_missing(&v.value))
+            .take(TOP_CATEGORIES)
+            .cloned()
+            .collect()
+    }
+}
+
+fn stat_shifts(old: &ColumnProfile, new: &ColumnProfile) -> Vec<StatShift> {
+    let summary = |col: &ColumnProfile| {
+        let stats = col.numeric.as_ref().expect("a numeric column part");
+        [
+            ("count", col.count as f64),
+            ("min", stats.min),
+            ("max", stats.max),
+            ("mean", stats.mean),
+            ("median", stats.median),
+            ("q1", stats.q1),
+            ("q3", stats.q3),
+        ]
+    };
+    summary(old)
+        .iter()
+        .zip(summary(new).iter())
+        .map(|((stat, old), (_, new))| StatShift {
+            stat: stat.to_string(),
+            old: *old,
+            new: *new,
+            delta: new - old,
+            relative: if *old != 0.0 {
+                Some((new - old) / old.abs())
+            } else {
+                None
+            },
+        })
+        .collect()
+}
+
+fn category_shift(old_top: Vec<ValueCount>, new_top: Vec<ValueCount>) -> CategoryShift {
+    let entered = new_top
+        .iter()
+        .filter(|n| !old_top.iter().any(|o| o.value == n.value))
+        .map(|n| n.value.clone())
+        .collect();
+    let left = old_top
+        .iter()
+        .filter(|o| !new_top.iter().any(|n| n.value == o.value))
+        .map(|o| o.value.clone())
+        .collect();
+    CategoryShift {
+        old_top,
+        new_top,
+        entered,
+        left,
+    }
+}
@@ -0,0 +1,537 @@
+// Distribution drift between a reference dataset and a current one:
+// PSI, Kolmogorov-Smirnov and Wasserstein for numeric columns,
+// PSI, chi-square and Jensen-Shannon divergence for categorical columns.
+use crate::analyze::{infer_column_type, is_missing};
+use crate::error::DataError;
+use crate::formats::{ColumnType, Dataset, read_dataset};
+use crate::report::{
+    HtmlOptions, embed_chart, save_overlay_bar_chart, save_overlay_histogram,
+    svg_overlay_bar_chart, svg_overlay_histogram,
+};
+use crate::templates::{ChartContext, TemplateKind, render};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Default HTML drift report template
+pub const DEFAULT_DRIFT_HTML_TEMPLATE: &str = include_str!("../templates/drift.html");
+/// Default Markdown drift report template
+pub const DEFAULT_DRIFT_MARKDOWN_TEMPLATE: &str = include_str!("../templates/drift.md");
+
+/// Number of reference-quantile bins used for the numeric PSI
+const PSI_BINS: usize = 10;
+/// Floor applied to empty bin shares so PSI stays finite
+const PSI_EPSILON: f64 = 1e-4;
+
+/// Drift severity, from the usual PSI rule of thumb: below 0.1 no significant
+/// shift, 0.1 to 0.25 moderate, above 0.25 major. A column whose type changed
+/// is always `High`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    None,
+    Moderate,
+    High,
+}
+
+impl Severity {
+    pub fn from_psi(psi: f64) -> Severity {
+        if psi < 0.1 {
+            Severity::None
+        } else if psi < 0.25 {
+            Severity::Moderate
+        } else {
+            Severity::High
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub reference_source: String,
+    pub current_source: String,
+    pub reference_rows: usize,
+    pub current_rows: usize,
+    /// Columns present in both datasets, in the current dataset's order
+    pub columns: Vec<ColumnDrift>,
+    /// Columns present in only one dataset, which cannot be compared
+    pub unmatched_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnDrift {
+    pub name: String,
+    pub reference_type: String,
+    pub current_type: String,
+    pub severity: Severity,
+    pub psi: Option<f64>,
+    pub ks_statistic: Option<f64>,
+    pub ks_p_value: Option<f64>,
+    pub wasserstein: Option<f64>,
+    pub chi_square: Option<f64>,
+    pub chi_square_p_value: Option<f64>,
+    pub js_divergence: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub charts: Vec<ChartContext>,
+}
+
+impl DriftReport {
+    /// Highest severity over all compared columns
+    pub fn max_severity(&self) -> Severity {
+        self.columns
+            .iter()
+            .map(|c| c.severity)
+            .max()
+            .unwrap_or(Severity::None)
+    }
+}
+
+/// Read both files and measure the drift of every shared column
+pub fn drift_files(
+    reference_path: &str,
+    current_path: &str,
+) -> Result<DriftReport, Box<dyn std::error::Error>> {
+    let reference = read_dataset(reference_path)?;
+    let current = read_dataset(current_path)?;
+    Ok(drift_datasets(&reference, &current, reference_path, current_path))
+}
+
+/// Measure the drift of every column present in both datasets, matched by name
+pub fn drift_datasets(
+    reference: &Dataset,
+    current: &Dataset,
+    reference_source: &str,
+    current_source: &str,
+) -> DriftReport {
+    let mut report = DriftReport {
+        reference_source: reference_source.to_string(),
+        current_source: current_source.to_string(),
+        reference_rows: reference.rows.len(),
+        current_rows: current.rows.len(),
+        columns: Vec::new(),
+        unmatched_columns: reference
+            .headers
+            .iter()
+            .filter(|h| !current.headers.contains(h))
+            .cloned()
+            .collect(),
+    };
+    for name in &current.headers {
+        let Some((ref_values, reference_type)) = present_values(reference, name) else {
+            report.unmatched_columns.push(name.clone());
+            continue;
+        };
+        let (cur_values, current_type) = present_values(current, name).unwrap_or_default();
+        let drift = column_drift(name, &ref_values, reference_type, &cur_values, current_type);
+        report.columns.push(drift);
+    }
+    report
+}
+
+/// The present values of a column with its type, `None` if the dataset has no
+/// such column. The type is the schema's when the file has one, so a NaN of a
+/// numeric column is missing too; otherwise it is guessed from the values.
+fn present_values(dataset: &Dataset, name: &str) -> Option<(Vec<String>, &'static str)> {
+    let i = dataset.headers.iter().position(|h| h == name)?;
+    let values = dataset.column(name)?.into_iter().filter(|v| !is_missing(v));
+    Some(match dataset.column_type(i) {
+        Some(ColumnType::Numeric) => {
+            let numbers = values.filter(|v| v.parse::<f64>().is_ok_and(|x| !x.is_nan()));
+            (numbers.collect(), "numeric")
+        }
+        Some(column_type) => (values.collect(), column_type.as_str()),
+        None => {
+            let values: Vec<String> = values.collect();
+            let column_type = infer_column_type(&values);
+            (values, column_type)
+        }
+    })
+}
+
+/// Compare the present values of a column in both datasets, each with its type
+fn column_drift(
+    name: &str,
+    reference: &[String],
+    reference_type: &str,
+    current: &[String],
+    current_type: &str,
+) -> ColumnDrift {
+    let mut drift = ColumnDrift {
+        name: name.to_string(),
+        reference_type: reference_type.to_string(),
+        current_type: current_type.to_string(),
+        severity: Severity::None,
+        psi: None,
+        ks_statistic: None,
+        ks_p_value: None,
+        wasserstein: None,
+        chi_square: None,
+        chi_square_p_value: None,
+        js_divergence: None,
+        charts: Vec::new(),
+    };
+    if reference.is_empty() || current.is_empty() {
+        return drift;
+    }
+    if reference_type != current_type {
+        drift.severity = Severity::High;
+        return drift;
+    }
+    if reference_type == "numeric" {
+        let r = numbers(reference);
+        let c = numbers(current);
+        let psi = psi_numeric(&r, &c);
+        let (d, p) = ks_test(&r, &c);
+        drift.psi = Some(psi);
+        drift.ks_statistic = Some(d);
+        drift.ks_p_value = Some(p);
+        drift.wasserstein = Some(wasserstein(&r, &c));
+        drift.severity = Severity::from_psi(psi);
+    } else {
+        let psi = psi_categorical(reference, current);
+        let (chi2, p) = chi_square_test(reference, current);
+        drift.psi = Some(psi);
+        drift.chi_square = Some(chi2);
+        drift.chi_square_p_value = Some(p);
+        drift.js_divergence = Some(js_divergence(reference, current));
+        drift.severity = Severity::from_psi(psi);
+    }
+    drift
+}
+
+/// Write the drift report as HTML, Markdown (`md`) or JSON. HTML and Markdown
+/// reports include an overlaid histogram (or grouped bar chart) per column.
+pub fn generate_drift_report(
+    report: &DriftReport,
+    reference: &Dataset,
+    current: &Dataset,
+    report_name: &str,
+    format: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = match format {
+        "json" => serde_json::to_string_pretty(report)? + "\n",
+        "html" | "md" | "markdown" => {
+            let chart_options = HtmlOptions {
+                self_contained: options.self_contained && format == "html",
+                ..options.clone()
+            };
+            let mut report = report.clone();
+            attach_drift_charts(&mut report, reference, current, report_name, &chart_options);
+            if format == "html" {
+                render(DEFAULT_DRIFT_HTML_TEMPLATE, TemplateKind::Html, &report)?
+            } else {
+                render(DEFAULT_DRIFT_MARKDOWN_TEMPLATE, TemplateKind::Markdown, &report)?
+            }
+        }
+        other => {
+            let message = format!("Unsupported drift report format '{}'", other);
+            return Err(DataError::UnsupportedFormat(message).into());
+        }
+    };
+    let mut file = crate::report::create_report_file(report_name)?;
+    file.write_all(output.as_bytes())?;
+    Ok(())
+}
+
+fn attach_drift_charts(
+    report: &mut DriftReport,
+    reference: &Dataset,
+    current: &Dataset,
+    report_name: &str,
+    options: &HtmlOptions,
+) {
+    if !options.charts {
+        return;
+    }
+    let report_dir = Path::new(report_name)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    for (i, column) in report.columns.iter_mut().enumerate() {
+        let name = column.name.as_str();
+        let (r, _) = present_values(reference, name).unwrap_or_default();
+        let (c, _) = present_values(current, name).unwrap_or_default();
+        if r.is_empty() || c.is_empty() || column.reference_type != column.current_type {
+            continue;
+        }
+        let chart = if column.reference_type == "numeric" {
+            let (r, c) = (numbers(&r), numbers(&c));
+            embed_chart(
+                options,
+                report_dir,
+                "drift_histogram",
+                &format!("drift_{}", i),
+                &format!("Distribution drift {}", name),
+                |f| save_overlay_histogram(f, &r, &c, name, &options.chart),
+                || svg_overlay_histogram(&r, &c, name, &options.chart),
+            )
+        } else {
+            embed_chart(
+                options,
+                report_dir,
+                "drift_bar",
+                &format!("drift_{}", i),
+                &format!("Distribution drift {}", name),
+                |f| save_overlay_bar_chart(f, &r, &c, name, &options.chart),
+                || svg_overlay_bar_chart(&r, &c, name, &options.chart),
+            )
+        };
+        match chart {
+            Ok(chart) => column.charts.push(chart),
+            Err(e) => eprintln!("Error generating drift chart: {}", e),
+        }
+    }
+}
+
+fn numbers(values: &[String]) -> Vec<f64> {
+    values.iter().filter_map(|v| v.parse::<f64>().ok()).collect()
+}
+
+fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+fn psi_from_shares(reference: &[f64], current: &[f64]) -> f64 {
+    reference
+        .iter()
+        .zip(current.iter())
+        .map(|(r, c)| {
+            let r = r.max(PSI_EPSILON);
+            let c = c.max(PSI_EPSILON);
+            (c - r) * (c / r).ln()
+        })
+        .sum()
+}
+
+/// Population Stability Index over bins cut at the reference deciles
+pub fn psi_numeric(reference: &[f64], current: &[f64]) -> f64 {
+    if reference.is_empty() || current.is_empty() {
+        return 0.0;
+    }
+    let sorted_ref = sorted(reference);
+    let mut edges: Vec<f64> = (1..PSI_BINS)
+        .map(|k| sorted_ref[(k * sorted_ref.len() / PSI_BINS).min(sorted_ref.len() - 1)])
+        .collect();
+    edges.dedup();
+    let shares = |data: &[f64]| {
+        let mut counts = vec![0usize; edges.len() + 1];
+        for v in data {
+            counts[edges.partition_point(|e| e < v)] += 1;
+        }
+        counts
+            .iter()
+            .map(|c| *c as f64 / data.len() as f64)
+            .collect::<Vec<_>>()
+    };
+    psi_from_shares(&shares(reference), &shares(current))
+}
+
+/// Category frequencies of both datasets over the union of their categories
+fn category_counts(reference: &[String], current: &[String]) -> (Vec<f64>, Vec<f64>) {
+    let mut counts: HashMap<&String, (f64, f64)> = HashMap::new();
+    for v in reference {
+        counts.entry(v).or_default().0 += 1.0;
+    }
+    for v in current {
+        counts.entry(v).or_default().1 += 1.0;
+    }
+    let mut keys: Vec<&&String> = counts.keys().collect();
+    keys.sort();
+    keys.iter().map(|k| counts[*k]).unzip()
+}
+
+/// Population Stability Index over the categories of both datasets
+pub fn psi_categorical(reference: &[String], current: &[String]) -> f64 {
+    if reference.is_empty() || current.is_empty() {
+        return 0.0;
+    }
+    let (r, c) = category_counts(reference, current);
+    let r_total = reference.len() as f64;
+    let c_total = current.len() as f64;
+    let r: Vec<f64> = r.iter().map(|v| v / r_total).collect();
+    let c: Vec<f64> = c.iter().map(|v| v / c_total).collect();
+    psi_from_shares(&r, &c)
+}
+
+/// Two-sample Kolmogorov-Smirnov test: the statistic D and its asymptotic p-value
+pub fn ks_test(reference: &[f64], current: &[f64]) -> (f64, f64) {
+    if reference.is_empty() || current.is_empty() {
+        return (0.0, 1.0);
+    }
+    let a = sorted(reference);
+    let b = sorted(current);
+    let (n, m) = (a.len() as f64, b.len() as f64);
+    let (mut i, mut j, mut d) = (0, 0, 0.0f64);
+    while i < a.len() && j < b.len() {
+        let x = a[i].min(b[j]);
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= x {
+            j += 1;
+        }
+        d = d.max((i as f64 / n - j as f64 / m).abs());
+    }
+    let en = (n * m / (n + m)).sqrt();
+    let lambda = (en + 0.12 + 0.11 / en) * d;
+    (d, kolmogorov_q(lambda))
+}
+
+/// Survival function of the Kolmogorov distribution
+fn kolmogorov_q(lambda: f64) -> f64 {
+    if lambda < 1e-3 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    let mut sign = 1.0;
+    for k in 1..=100 {
+        let term = sign * (-2.0 * (k as f64 * lambda).powi(2)).exp();
+        sum += term;
+        if term.abs() < 1e-12 {
+            break;
+        }
+        sign = -sign;
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// First Wasserstein (earth mover's) distance between two empirical distributions
+pub fn wasserstein(reference: &[f64], current: &[f64]) -> f64 {
+    if reference.is_empty() || current.is_empty() {
+        return 0.0;
+    }
+    let a = sorted(reference);
+    let b = sorted(current);
+    let mut points: Vec<f64> = a.iter().chain(b.iter()).cloned().collect();
+    points.sort_by(f64::total_cmp);
+    let (n, m) = (a.len() as f64, b.len() as f64);
+    let (mut i, mut j, mut distance) = (0, 0, 0.0);
+    for w in points.windows(2) {
+        while i < a.len() && a[i] <= w[0] {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= w[0] {
+            j += 1;
+        }
+        distance += (i as f64 / n - j as f64 / m).abs() * (w[1] - w[0]);
+    }
+    distance
+}
+
+/// Chi-square test of homogeneity on the 2 x k table of category counts
+pub fn chi_square_test(reference: &[String], current: &[String]) -> (f64, f64) {
+    let (r, c) = category_counts(reference, current);
+    if r.len() < 2 {
+        return (0.0, 1.0);
+    }
+    let r_total: f64 = r.iter().sum();
+    let c_total: f64 = c.iter().sum();
+    let total = r_total + c_total;
+    let mut chi2 = 0.0;
+    for (ro, co) in r.iter().zip(c.iter()) {
+        let col_total = ro + co;
+        for (observed, row_total) in [(ro, r_total), (co, c_total)] {
+            let expected = row_total * col_total / total;
+            if expected > 0.0 {
+                chi2 += (observed - expected).powi(2) / expected;
+            }
+        }
+    }
+    let dof = (r.len() - 1) as f64;
+    (chi2, gamma_q(dof / 2.0, chi2 / 2.0))
+}
+
+/// Jensen-Shannon divergence (base 2, between 0 and 1) of the category shares
+pub fn js_divergence(reference: &[String], current: &[String]) -> f64 {
+    if reference.is_empty() || current.is_empty() {
+        return 0.0;
+    }
+    let (r, c) = category_counts(reference, current);
+    let r_total = reference.len() as f64;
+    let c_total = current.len() as f64;
+    let mut js = 0.0;
+    for (r, c) in r.iter().zip(c.iter()) {
+        let p = r / r_total;
+        let q = c / c_total;
+        let m = (p + q) / 2.0;
+        if p > 0.0 {
+            js += 0.5 * p * (p / m).log2();
+        }
+        if q > 0.0 {
+            js += 0.5 * q * (q / m).log2();
+        }
+    }
+    js.clamp(0.0, 1.0)
+}
+
+/// Natural log of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut ser = 1.000000000190015;
+    for (k, c) in COEF.iter().enumerate() {
+        ser += c / (x + 1.0 + k as f64);
+    }
+    -tmp + (2.5066282746310005 * ser / x).ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x)
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    if x < a + 1.0 {
+        // Series expansion of P(a, x)
+        let mut sum = 1.0 / a;
+        let mut term = sum;
+        let mut ap = a;
+        for _ in 0..500 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-14 {
+                break;
+            }
+        }
+        1.0 - sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    } else {
+        // Continued fraction for Q(a, x)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-14 {
+                break;
+            }
+        }
+        (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    }
+    .clamp(0.0, 1.0)
+}
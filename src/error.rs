@@ -0,0 +1,24 @@
+// Error kinds that callers can tell apart. Everything else is reported as a
+// plain boxed error whose message is shown to the user.
+use std::fmt;
+
+/// A failure of a kind callers (the Python bindings in particular) act on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataError {
+    /// The input or report format is not one datastory reads or writes
+    UnsupportedFormat(String),
+    /// The input does not parse as the format it was read as
+    Parse(String),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::UnsupportedFormat(message) | DataError::Parse(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
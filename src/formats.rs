@@ -1,8 +1,18 @@
+use crate::sniff::{
+    CsvDialect, InputFormat, SNIFF_LEN, detect_encoding, detect_format, read_prefix, sniff_csv_text,
+};
+use crate::compression;
+use crate::error::DataError;
+use crate::sample::{SampleInfo, Sampling};
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
 use parquet::record::RowAccessor;
-// File format readers: CSV, Parquet, JSON
+use std::io::{BufRead, BufReader, Read};
+// File format readers: CSV, Parquet, JSON, NDJSON, spreadsheets, Arrow IPC and
+// Avro; any of them may be gzip, zstd, bzip2 or xz compressed
 
 pub fn read_csv(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
-    let mut rdr = csv::ReaderBuilder::new().from_path(path)?;
+    let mut rdr = csv::ReaderBuilder::new().from_reader(compression::open(path)?);
     let mut data = Vec::new();
     for result in rdr.records() {
         let record = result?;
@@ -12,10 +22,7 @@ pub fn read_csv(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::Erro
 }
 
 pub fn read_parquet(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
-    use parquet::file::reader::{FileReader, SerializedFileReader};
-    use std::fs::File;
-    let file = File::open(path)?;
-    let reader = SerializedFileReader::new(file)?;
+    let reader = open_parquet(path)?;
     let mut data = Vec::new();
     for record in reader.get_row_iter(None)? {
         let mut row = Vec::new();
@@ -28,9 +35,7 @@ pub fn read_parquet(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::
 }
 
 pub fn read_json(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
-    use std::fs;
-    let content = fs::read_to_string(path)?;
-    let json: serde_json::Value = serde_json::from_str(&content)?;
+    let json: serde_json::Value = serde_json::from_reader(compression::open(path)?)?;
     let mut data = Vec::new();
     if let Some(arr) = json.as_array() {
         for obj in arr {
@@ -45,3 +50,867 @@ pub fn read_json(path: &str) -> Result<Vec<Vec<String>>, Box<dyn std::error::Err
     }
     Ok(data)
 }
+
+/// A table read from any supported file: column names plus string cells, and
+/// the numeric columns of Arrow and Avro files as numbers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dataset {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// How the rows were sampled from the source, when they are a sample
+    pub sample: Option<SampleInfo>,
+    /// Column types given by the file's schema (Parquet, Arrow, Avro); the
+    /// types of other files are guessed from their values
+    pub types: Option<Vec<ColumnType>>,
+    /// Columns read as numbers from a typed file (`None` for the others);
+    /// their cells in `rows` are left empty
+    pub numbers: Option<Vec<Option<Numbers>>>,
+}
+
+/// Type a file's schema gives a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Numeric,
+    Boolean,
+    Date,
+    /// Strings, timestamps, binary and nested values
+    Text,
+}
+
+impl ColumnType {
+    /// The column type as profiles name it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColumnType::Numeric => "numeric",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+            ColumnType::Text => "categorical",
+        }
+    }
+}
+
+/// The values of a numeric column as its file stores them, so that none is
+/// rounded through text; `None` for nulls
+#[derive(Debug, Clone, PartialEq)]
+pub enum Numbers {
+    Int(Vec<Option<i64>>),
+    UInt(Vec<Option<u64>>),
+    Float32(Vec<Option<f32>>),
+    Float(Vec<Option<f64>>),
+    /// Unscaled values and their scale (digits after the point)
+    Decimal(Vec<Option<i128>>, i32),
+}
+
+impl Numbers {
+    pub fn len(&self) -> usize {
+        match self {
+            Numbers::Int(v) => v.len(),
+            Numbers::UInt(v) => v.len(),
+            Numbers::Float32(v) => v.len(),
+            Numbers::Float(v) => v.len(),
+            Numbers::Decimal(v, _) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Value `i` as analysed; `None` for a null or NaN
+    pub fn value(&self, i: usize) -> Option<f64> {
+        let value = match self {
+            Numbers::Int(v) => v[i]? as f64,
+            Numbers::UInt(v) => v[i]? as f64,
+            Numbers::Float32(v) => f64::from(v[i]?),
+            Numbers::Float(v) => v[i]?,
+            Numbers::Decimal(v, scale) => v[i]? as f64 / 10f64.powi(*scale),
+        };
+        (!value.is_nan()).then_some(value)
+    }
+
+    /// Value `i` written exactly, as a cell of the column; nulls are empty
+    pub fn cell(&self, i: usize) -> String {
+        match self {
+            Numbers::Int(v) => v[i].map(|x| x.to_string()),
+            Numbers::UInt(v) => v[i].map(|x| x.to_string()),
+            Numbers::Float32(v) => v[i].map(|x| format!("{:?}", x)),
+            Numbers::Float(v) => v[i].map(|x| format!("{:?}", x)),
+            Numbers::Decimal(v, scale) => v[i].map(|x| decimal_text(x, *scale)),
+        }
+        .unwrap_or_default()
+    }
+
+    pub fn push_null(&mut self) {
+        match self {
+            Numbers::Int(v) => v.push(None),
+            Numbers::UInt(v) => v.push(None),
+            Numbers::Float32(v) => v.push(None),
+            Numbers::Float(v) => v.push(None),
+            Numbers::Decimal(v, _) => v.push(None),
+        }
+    }
+
+    /// The values at `indices`, in that order
+    pub fn select(&self, indices: &[usize]) -> Numbers {
+        match self {
+            Numbers::Int(v) => Numbers::Int(indices.iter().map(|&i| v[i]).collect()),
+            Numbers::UInt(v) => Numbers::UInt(indices.iter().map(|&i| v[i]).collect()),
+            Numbers::Float32(v) => Numbers::Float32(indices.iter().map(|&i| v[i]).collect()),
+            Numbers::Float(v) => Numbers::Float(indices.iter().map(|&i| v[i]).collect()),
+            Numbers::Decimal(v, scale) => {
+                Numbers::Decimal(indices.iter().map(|&i| v[i]).collect(), *scale)
+            }
+        }
+    }
+
+    pub fn truncate(&mut self, n: usize) {
+        match self {
+            Numbers::Int(v) => v.truncate(n),
+            Numbers::UInt(v) => v.truncate(n),
+            Numbers::Float32(v) => v.truncate(n),
+            Numbers::Float(v) => v.truncate(n),
+            Numbers::Decimal(v, _) => v.truncate(n),
+        }
+    }
+}
+
+/// An unscaled decimal written with `scale` digits after the point
+pub(crate) fn decimal_text(unscaled: i128, scale: i32) -> String {
+    if scale <= 0 {
+        let zeros = if unscaled == 0 { 0 } else { scale.unsigned_abs() as usize };
+        return format!("{}{}", unscaled, "0".repeat(zeros));
+    }
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", unscaled.unsigned_abs(), width = scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    let sign = if unscaled < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, int, frac)
+}
+
+impl Dataset {
+    /// Split every column into its numeric values and its remaining (categorical)
+    /// values. Columns typed by a schema are not guessed: the values of a text
+    /// column all stay text, even `00123`, and a NaN in a numeric one is no
+    /// number to analyse but a missing value.
+    pub fn split_columns(&self) -> (Vec<Vec<f64>>, Vec<Vec<String>>) {
+        let col_count = self.headers.len();
+        let mut num_columns: Vec<Vec<f64>> = vec![vec![]; col_count];
+        let mut cat_columns: Vec<Vec<String>> = vec![vec![]; col_count];
+        for (i, numbers) in (0..col_count).filter_map(|i| Some((i, self.numbers(i)?))) {
+            for k in 0..numbers.len() {
+                match numbers.value(k) {
+                    Some(num) => num_columns[i].push(num),
+                    None => cat_columns[i].push(String::new()),
+                }
+            }
+        }
+        for row in &self.rows {
+            for (i, value) in row.iter().enumerate().take(col_count) {
+                if self.numbers(i).is_some() {
+                    continue;
+                }
+                let numeric = self.column_type(i).is_none_or(|t| t == ColumnType::Numeric);
+                match value.parse::<f64>() {
+                    Ok(num) if numeric && !num.is_nan() => num_columns[i].push(num),
+                    Ok(_) if numeric && self.column_type(i).is_some() => {
+                        cat_columns[i].push(String::new())
+                    }
+                    _ => cat_columns[i].push(value.to_string()),
+                }
+            }
+        }
+        (num_columns, cat_columns)
+    }
+
+    /// Schema type of column `i`; `None` when the file has no schema
+    pub fn column_type(&self, i: usize) -> Option<ColumnType> {
+        self.types.as_ref().and_then(|types| types.get(i).copied())
+    }
+
+    /// Values of column `i` when they were read as numbers
+    pub fn numbers(&self, i: usize) -> Option<&Numbers> {
+        self.numbers.as_ref()?.get(i)?.as_ref()
+    }
+
+    /// All cells of the named column, `None` if there is no such column
+    pub fn column(&self, name: &str) -> Option<Vec<String>> {
+        let idx = self.headers.iter().position(|h| h == name)?;
+        if let Some(numbers) = self.numbers(idx) {
+            return Some((0..numbers.len()).map(|k| numbers.cell(k)).collect());
+        }
+        Some(
+            self.rows
+                .iter()
+                .map(|row| row.get(idx).cloned().unwrap_or_default())
+                .collect(),
+        )
+    }
+
+    /// Keep only the `include` columns (all when empty), minus the `exclude` ones.
+    /// Unknown column names are an error so typos do not go unnoticed.
+    pub fn select_columns(
+        &self,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Dataset, Box<dyn std::error::Error>> {
+        for name in include.iter().chain(exclude) {
+            if !self.headers.contains(name) {
+                return Err(format!("Unknown column '{}'", name).into());
+            }
+        }
+        let keep: Vec<usize> = (0..self.headers.len())
+            .filter(|&i| include.is_empty() || include.contains(&self.headers[i]))
+            .filter(|&i| !exclude.contains(&self.headers[i]))
+            .collect();
+        Ok(Dataset {
+            headers: keep.iter().map(|&i| self.headers[i].clone()).collect(),
+            rows: self
+                .rows
+                .iter()
+                .map(|row| {
+                    keep.iter()
+                        .map(|&i| row.get(i).cloned().unwrap_or_default())
+                        .collect()
+                })
+                .collect(),
+            sample: self.sample.clone(),
+            types: self
+                .types
+                .as_ref()
+                .map(|types| keep.iter().map(|&i| types[i]).collect()),
+            numbers: self
+                .numbers
+                .as_ref()
+                .map(|numbers| keep.iter().map(|&i| numbers[i].clone()).collect()),
+        })
+    }
+
+    /// Drop every row after the first `n`
+    pub fn truncate_rows(&mut self, n: usize) {
+        self.rows.truncate(n);
+        for numbers in self.numbers.iter_mut().flatten().flatten() {
+            numbers.truncate(n);
+        }
+    }
+}
+
+/// How delimited text is read; unset options are detected from the content
+#[derive(Debug, Clone, Default)]
+pub struct CsvOptions {
+    /// Field delimiter
+    pub delimiter: Option<u8>,
+    /// Quote character
+    pub quote: Option<u8>,
+    /// Escape character inside quoted fields; quotes are escaped by doubling them when unset
+    pub escape: Option<u8>,
+    /// Whether the first (non-skipped) row holds the column names
+    pub has_headers: Option<bool>,
+    /// Lines skipped at the start of the file, before the header
+    pub skip_rows: usize,
+    /// Lines starting with this character are ignored
+    pub comment: Option<u8>,
+    /// Accept rows with fewer or more fields than the header
+    pub flexible: bool,
+    /// Input encoding; see [`parse_encoding`]
+    pub encoding: Option<&'static Encoding>,
+}
+
+impl CsvOptions {
+    /// Dialect of a file: the explicit options, the rest sniffed from its first bytes
+    pub fn resolve(&self, prefix: &[u8]) -> CsvDialect {
+        let encoding = self.encoding.unwrap_or_else(|| detect_encoding(prefix).0);
+        let (text, encoding, _) = encoding.decode(prefix);
+        let body: String = skip_lines(&text, self.skip_rows)
+            .split_inclusive('\n')
+            .filter(|line| self.comment.is_none_or(|c| !line.as_bytes().starts_with(&[c])))
+            .collect();
+        let sniffed = sniff_csv_text(&body, prefix.len() >= SNIFF_LEN);
+        CsvDialect {
+            delimiter: self.delimiter.unwrap_or(sniffed.delimiter),
+            quote: self.quote.unwrap_or(sniffed.quote),
+            has_headers: self.has_headers.unwrap_or(sniffed.has_headers),
+            encoding,
+        }
+    }
+}
+
+/// Encoding from a WHATWG label such as `utf-8`, `latin1`, `windows-1252` or `utf-16le`
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| format!("unknown encoding '{}'", label))
+}
+
+/// Options applied while reading a dataset; unset fields are detected from the content
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Input format, overriding content sniffing
+    pub format: Option<InputFormat>,
+    /// Dialect of CSV files
+    pub csv: CsvOptions,
+    /// Sheet of a workbook, by name or 1-based position; the first sheet when unset
+    pub sheet: Option<String>,
+    /// Sample of the rows to keep; CSV and NDJSON rows are sampled while they
+    /// are read when the method allows it
+    pub sample: Option<Sampling>,
+}
+
+/// One sheet of a workbook; plain data files are a single sheet with an empty name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub dataset: Dataset,
+}
+
+/// Read a CSV, Parquet, JSON, NDJSON, spreadsheet, Arrow or Avro file together with its column names.
+/// The format is detected from the file content, whatever its extension.
+pub fn read_dataset(path: &str) -> Result<Dataset, Box<dyn std::error::Error>> {
+    read_dataset_with_options(path, &ReadOptions::default())
+}
+
+pub fn read_dataset_with_options(
+    path: &str,
+    options: &ReadOptions,
+) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let prefix = read_prefix(path).map_err(|e| cannot_read(path, e))?;
+    // Streaming readers sample as they go; the other formats are sampled once read
+    let streamed = options.sample.as_ref().filter(|s| s.streams());
+    let dataset = match options.format.unwrap_or_else(|| detect_format(&prefix)) {
+        InputFormat::Csv => {
+            let dialect = options.csv.resolve(&prefix);
+            let dataset = read_csv_dataset(path, &dialect, &options.csv, streamed)?;
+            if streamed.is_some() {
+                return Ok(dataset);
+            }
+            dataset
+        }
+        InputFormat::Ndjson if streamed.is_some() => return read_ndjson_dataset(path, streamed),
+        InputFormat::Ndjson => read_ndjson_dataset(path, None)?,
+        InputFormat::Parquet => read_parquet_dataset(path)?,
+        InputFormat::Json => read_json_dataset(path)?,
+        InputFormat::Spreadsheet => {
+            let mut sheets = read_workbook(path, Some(options.sheet.as_deref().unwrap_or("1")))?;
+            sheets.remove(0).dataset
+        }
+        InputFormat::Arrow => read_arrow_dataset(path, &prefix)?,
+        InputFormat::Avro => crate::avro::read_avro(compression::open(path)?)
+            .map_err(|e| DataError::Parse(format!("Cannot read '{}': {}", path, e)))?,
+    };
+    sample_dataset(dataset, options)
+}
+
+fn sample_dataset(
+    dataset: Dataset,
+    options: &ReadOptions,
+) -> Result<Dataset, Box<dyn std::error::Error>> {
+    match &options.sample {
+        Some(sampling) => sampling.apply(dataset),
+        None => Ok(dataset),
+    }
+}
+
+/// Read every sheet of a workbook (only the selected one when `options.sheet`
+/// is set); any other file comes back as a single unnamed sheet
+pub fn read_sheets(path: &str, options: &ReadOptions) -> Result<Vec<Sheet>, Box<dyn std::error::Error>> {
+    let prefix = read_prefix(path).map_err(|e| cannot_read(path, e))?;
+    if options.format.unwrap_or_else(|| detect_format(&prefix)) == InputFormat::Spreadsheet {
+        return read_workbook(path, options.sheet.as_deref())?
+            .into_iter()
+            .map(|sheet| {
+                Ok(Sheet {
+                    name: sheet.name,
+                    dataset: sample_dataset(sheet.dataset, options)?,
+                })
+            })
+            .collect();
+    }
+    Ok(vec![Sheet {
+        name: String::new(),
+        dataset: read_dataset_with_options(path, options)?,
+    }])
+}
+
+/// An I/O error naming the file, keeping its kind (e.g. not found)
+fn cannot_read(path: &str, e: std::io::Error) -> std::io::Error {
+    std::io::Error::new(e.kind(), format!("Cannot read '{}': {}", path, e))
+}
+
+/// Buffered UTF-8 text of a (possibly compressed) file, decoded from `encoding`
+/// on the fly; a byte order mark overrides `encoding` and is removed
+fn open_text(path: &str, encoding: &'static Encoding) -> std::io::Result<impl BufRead> {
+    let decoder = DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .bom_override(true)
+        .build(compression::open(path)?);
+    Ok(BufReader::new(decoder))
+}
+
+/// The text after its first `n` lines
+fn skip_lines(text: &str, n: usize) -> &str {
+    let mut rest = text;
+    for _ in 0..n {
+        match rest.find('\n') {
+            Some(end) => rest = &rest[end + 1..],
+            None => return "",
+        }
+    }
+    rest
+}
+
+fn read_csv_dataset(
+    path: &str,
+    dialect: &CsvDialect,
+    options: &CsvOptions,
+    sample: Option<&Sampling>,
+) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let mut text = open_text(path, dialect.encoding)?;
+    let mut line = Vec::new();
+    for _ in 0..options.skip_rows {
+        line.clear();
+        if text.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+    }
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .escape(options.escape)
+        .double_quote(options.escape.is_none())
+        .comment(options.comment)
+        .flexible(options.flexible)
+        .has_headers(dialect.has_headers)
+        .from_reader(text);
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut sampler = sample.map(Sampling::sampler);
+    for result in rdr.records() {
+        let record = result?;
+        let row = record.iter().map(|s| s.to_string()).collect();
+        match sampler.as_mut() {
+            Some(sampler) => {
+                sampler.push(row);
+                if sampler.is_full() {
+                    break;
+                }
+            }
+            None => rows.push(row),
+        }
+    }
+    let mut sample = None;
+    if let Some(sampler) = sampler {
+        (rows, sample) = sampler.finish();
+    }
+    let mut headers: Vec<String> = if dialect.has_headers {
+        rdr.headers()?.iter().map(|s| s.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+    // Ragged rows: name any extra columns and pad short rows with missing cells
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0).max(headers.len());
+    for i in headers.len()..width {
+        headers.push(format!("column_{}", i + 1));
+    }
+    for row in rows.iter_mut() {
+        row.resize(width, String::new());
+    }
+    Ok(Dataset {
+        headers,
+        rows,
+        sample,
+        types: None,
+        numbers: None,
+    })
+}
+
+/// Parquet needs random access, so a compressed file (or standard input) is read into memory
+fn open_parquet(
+    path: &str,
+) -> Result<Box<dyn parquet::file::reader::FileReader>, Box<dyn std::error::Error>> {
+    use parquet::file::reader::SerializedFileReader;
+    if path != compression::STDIN
+        && compression::Compression::detect(path)? == compression::Compression::None
+    {
+        return Ok(Box::new(SerializedFileReader::new(std::fs::File::open(path)?)?));
+    }
+    let mut data = Vec::new();
+    compression::open(path)?.read_to_end(&mut data)?;
+    Ok(Box::new(SerializedFileReader::new(bytes::Bytes::from(data))?))
+}
+
+fn read_parquet_dataset(path: &str) -> Result<Dataset, Box<dyn std::error::Error>> {
+    use parquet::record::Field;
+    let reader = open_parquet(path)?;
+    let fields = reader.metadata().file_metadata().schema().get_fields().to_vec();
+    let headers = fields.iter().map(|f| f.name().to_string()).collect();
+    let types = fields.iter().map(|f| parquet_column_type(f)).collect();
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+    let mut rows = Vec::new();
+    for record in reader.get_row_iter(None)? {
+        rows.push(
+            record
+                .get_column_iter()
+                .map(|(_, field)| match field {
+                    Field::Null => String::new(),
+                    Field::Str(s) => s.clone(),
+                    Field::Date(days) => (epoch + chrono::Duration::days(i64::from(*days)))
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                    other => other.to_string(),
+                })
+                .collect(),
+        );
+    }
+    Ok(Dataset {
+        headers,
+        rows,
+        sample: None,
+        types: Some(types),
+        numbers: None,
+    })
+}
+
+/// Column type of a top-level Parquet field, from its logical (or legacy
+/// converted) type and its physical type
+fn parquet_column_type(field: &parquet::schema::types::Type) -> ColumnType {
+    use parquet::basic::{ConvertedType, LogicalType, Type as PhysicalType};
+    if !field.is_primitive() {
+        return ColumnType::Text;
+    }
+    let info = field.get_basic_info();
+    if let Some(logical) = info.logical_type() {
+        return match logical {
+            LogicalType::Date => ColumnType::Date,
+            LogicalType::Decimal { .. } | LogicalType::Integer { .. } => ColumnType::Numeric,
+            _ => ColumnType::Text,
+        };
+    }
+    match info.converted_type() {
+        ConvertedType::DATE => ColumnType::Date,
+        ConvertedType::DECIMAL
+        | ConvertedType::INT_8
+        | ConvertedType::INT_16
+        | ConvertedType::INT_32
+        | ConvertedType::INT_64
+        | ConvertedType::UINT_8
+        | ConvertedType::UINT_16
+        | ConvertedType::UINT_32
+        | ConvertedType::UINT_64 => ColumnType::Numeric,
+        ConvertedType::NONE => match field.get_physical_type() {
+            PhysicalType::BOOLEAN => ColumnType::Boolean,
+            PhysicalType::INT32 | PhysicalType::INT64 | PhysicalType::FLOAT | PhysicalType::DOUBLE => {
+                ColumnType::Numeric
+            }
+            _ => ColumnType::Text,
+        },
+        _ => ColumnType::Text,
+    }
+}
+
+fn read_json_dataset(path: &str) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let json: serde_json::Value = serde_json::from_reader(open_text(path, encoding_rs::UTF_8)?)?;
+    let objects: Vec<&serde_json::Map<String, serde_json::Value>> = json
+        .as_array()
+        .ok_or_else(|| DataError::Parse("Expected a JSON array of objects".to_string()))?
+        .iter()
+        .filter_map(|v| v.as_object())
+        .collect();
+    Ok(objects_to_dataset(&objects))
+}
+
+/// Every line is parsed, even when only a sample of them is kept, unless the
+/// first lines make up the sample
+fn read_ndjson_dataset(
+    path: &str,
+    sample: Option<&Sampling>,
+) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let mut records = Vec::new();
+    let mut sampler = sample.map(Sampling::sampler);
+    for (i, line) in open_text(path, encoding_rs::UTF_8)?.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|e| DataError::Parse(format!("Invalid JSON on line {}: {}", i + 1, e)))?;
+        match sampler.as_mut() {
+            Some(sampler) if value.is_object() => {
+                sampler.push(value);
+                if sampler.is_full() {
+                    break;
+                }
+            }
+            _ => records.push(value),
+        }
+    }
+    let mut sample = None;
+    if let Some(sampler) = sampler {
+        (records, sample) = sampler.finish();
+    }
+    let objects: Vec<&serde_json::Map<String, serde_json::Value>> =
+        records.iter().filter_map(|v| v.as_object()).collect();
+    Ok(Dataset {
+        sample,
+        ..objects_to_dataset(&objects)
+    })
+}
+
+/// Read an Arrow IPC file (Feather v2) or stream
+fn read_arrow_dataset(path: &str, prefix: &[u8]) -> Result<Dataset, Box<dyn std::error::Error>> {
+    use arrow_ipc::reader::{FileReader, StreamReader};
+    if prefix.starts_with(b"ARROW1") {
+        // The file format ends with a footer, so it needs random access
+        let mut data = Vec::new();
+        compression::open(path)?.read_to_end(&mut data)?;
+        arrow_dataset(FileReader::try_new(std::io::Cursor::new(data), None)?)
+    } else {
+        arrow_dataset(StreamReader::try_new(compression::open(path)?, None)?)
+    }
+}
+
+/// Collect Arrow record batches (from an IPC file or the Arrow C stream of a
+/// DataFrame). Column names and types come from the schema. Integer, float and
+/// decimal columns are copied from their arrays as numbers; the other values
+/// are written as text: dates as `YYYY-MM-DD`, timestamps as
+/// `YYYY-MM-DD HH:MM:SS` and nulls as missing cells.
+pub fn arrow_dataset(
+    reader: impl arrow_array::RecordBatchReader,
+) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let schema = reader.schema();
+    let headers = schema.fields().iter().map(|f| f.name().clone()).collect();
+    let types = schema.fields().iter().map(|f| arrow_column_type(f.data_type())).collect();
+    let mut numbers: Vec<Option<Numbers>> =
+        schema.fields().iter().map(|f| arrow_numbers(f.data_type())).collect();
+    let options = arrow_cast::display::FormatOptions::new()
+        .with_null("")
+        .with_date_format(Some("%Y-%m-%d"))
+        .with_datetime_format(Some("%Y-%m-%d %H:%M:%S%.f"))
+        .with_timestamp_format(Some("%Y-%m-%d %H:%M:%S%.f"))
+        .with_timestamp_tz_format(Some("%Y-%m-%d %H:%M:%S%.f"));
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let mut formatters = Vec::new();
+        for (column, numbers) in batch.columns().iter().zip(numbers.iter_mut()) {
+            match numbers {
+                Some(numbers) => {
+                    extend_numbers(numbers, column.as_ref());
+                    formatters.push(None);
+                }
+                None => formatters.push(Some(arrow_cast::display::ArrayFormatter::try_new(
+                    column.as_ref(),
+                    &options,
+                )?)),
+            }
+        }
+        for i in 0..batch.num_rows() {
+            rows.push(
+                formatters
+                    .iter()
+                    .map(|f| f.as_ref().map_or_else(String::new, |f| f.value(i).to_string()))
+                    .collect(),
+            );
+        }
+    }
+    Ok(Dataset {
+        headers,
+        rows,
+        sample: None,
+        types: Some(types),
+        numbers: Some(numbers),
+    })
+}
+
+/// An empty store for an Arrow column read as numbers; `None` for the
+/// columns written as text
+fn arrow_numbers(data_type: &arrow_schema::DataType) -> Option<Numbers> {
+    use arrow_schema::DataType;
+    Some(match data_type {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            Numbers::Int(Vec::new())
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            Numbers::UInt(Vec::new())
+        }
+        DataType::Float32 => Numbers::Float32(Vec::new()),
+        DataType::Float64 => Numbers::Float(Vec::new()),
+        DataType::Decimal128(_, scale) => Numbers::Decimal(Vec::new(), i32::from(*scale)),
+        _ => return None,
+    })
+}
+
+/// Append the values of an array to the store [`arrow_numbers`] made for its type
+fn extend_numbers(numbers: &mut Numbers, array: &dyn arrow_array::Array) {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::*;
+    use arrow_schema::DataType;
+    match (numbers, array.data_type()) {
+        (Numbers::Int(v), DataType::Int8) => {
+            v.extend(array.as_primitive::<Int8Type>().iter().map(|x| x.map(i64::from)))
+        }
+        (Numbers::Int(v), DataType::Int16) => {
+            v.extend(array.as_primitive::<Int16Type>().iter().map(|x| x.map(i64::from)))
+        }
+        (Numbers::Int(v), DataType::Int32) => {
+            v.extend(array.as_primitive::<Int32Type>().iter().map(|x| x.map(i64::from)))
+        }
+        (Numbers::Int(v), _) => v.extend(array.as_primitive::<Int64Type>().iter()),
+        (Numbers::UInt(v), DataType::UInt8) => {
+            v.extend(array.as_primitive::<UInt8Type>().iter().map(|x| x.map(u64::from)))
+        }
+        (Numbers::UInt(v), DataType::UInt16) => {
+            v.extend(array.as_primitive::<UInt16Type>().iter().map(|x| x.map(u64::from)))
+        }
+        (Numbers::UInt(v), DataType::UInt32) => {
+            v.extend(array.as_primitive::<UInt32Type>().iter().map(|x| x.map(u64::from)))
+        }
+        (Numbers::UInt(v), _) => v.extend(array.as_primitive::<UInt64Type>().iter()),
+        (Numbers::Float32(v), _) => v.extend(array.as_primitive::<Float32Type>().iter()),
+        (Numbers::Float(v), _) => v.extend(array.as_primitive::<Float64Type>().iter()),
+        (Numbers::Decimal(v, _), _) => v.extend(array.as_primitive::<Decimal128Type>().iter()),
+    }
+}
+
+/// Column type of an Arrow data type; dictionary columns take the type of their values
+fn arrow_column_type(data_type: &arrow_schema::DataType) -> ColumnType {
+    use arrow_schema::DataType;
+    match data_type {
+        DataType::Boolean => ColumnType::Boolean,
+        DataType::Date32 | DataType::Date64 => ColumnType::Date,
+        DataType::Dictionary(_, values) => arrow_column_type(values),
+        t if t.is_numeric() => ColumnType::Numeric,
+        _ => ColumnType::Text,
+    }
+}
+
+/// Read the `sheet` named (or numbered from 1) of an XLSX, XLSM, XLSB, XLS or
+/// ODS workbook, or all its non-empty sheets when `sheet` is `None`
+fn read_workbook(path: &str, sheet: Option<&str>) -> Result<Vec<Sheet>, Box<dyn std::error::Error>> {
+    use calamine::Reader;
+    let mut data = Vec::new();
+    compression::open(path)?.read_to_end(&mut data)?;
+    let mut workbook = calamine::open_workbook_auto_from_rs(std::io::Cursor::new(data))?;
+    let names = workbook.sheet_names();
+    let selected: Vec<String> = match sheet {
+        None => names.clone(),
+        Some(wanted) => {
+            let by_position = wanted
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| names.get(n.checked_sub(1)?));
+            let name = names
+                .iter()
+                .find(|n| n.as_str() == wanted)
+                .or(by_position)
+                .ok_or_else(|| {
+                    format!("No sheet '{}' in '{}' (sheets: {})", wanted, path, names.join(", "))
+                })?;
+            vec![name.clone()]
+        }
+    };
+    let mut sheets = Vec::new();
+    for name in selected {
+        let range = workbook.worksheet_range(&name)?;
+        let rows: Vec<&[calamine::Data]> = range.rows().collect();
+        let dataset = sheet_dataset(&rows);
+        if sheet.is_none() && dataset.headers.is_empty() {
+            continue;
+        }
+        sheets.push(Sheet { name, dataset });
+    }
+    if sheets.is_empty() {
+        return Err(format!("No data in any sheet of '{}'", path).into());
+    }
+    Ok(sheets)
+}
+
+/// Rows of sheet cells as a dataset. The header row is the first of the widest
+/// rows near the top (titles and notes above it are skipped) when all its
+/// filled cells are text; otherwise the sheet has no header row.
+fn sheet_dataset(rows: &[&[calamine::Data]]) -> Dataset {
+    use calamine::Data;
+    let filled = |row: &[Data]| row.iter().filter(|c| !matches!(c, Data::Empty)).count();
+    let rows: Vec<&[Data]> = rows.iter().copied().filter(|r| filled(r) > 0).collect();
+    let scan = &rows[..rows.len().min(10)];
+    let widest = scan.iter().map(|r| filled(r)).max().unwrap_or(0);
+    let Some(start) = scan.iter().position(|r| filled(r) == widest) else {
+        return Dataset::default();
+    };
+    let has_header = rows.len() > start + 1
+        && rows[start]
+            .iter()
+            .all(|c| matches!(c, Data::String(_) | Data::Empty));
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let headers = (0..width)
+        .map(|i| match rows[start].get(i) {
+            Some(Data::String(s)) if has_header && !s.trim().is_empty() => s.trim().to_string(),
+            _ => format!("column_{}", i + 1),
+        })
+        .collect();
+    let first_data = if has_header { start + 1 } else { start };
+    let rows = rows[first_data..]
+        .iter()
+        .map(|row| {
+            (0..width)
+                .map(|i| row.get(i).map(sheet_cell).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    Dataset {
+        headers,
+        rows,
+        sample: None,
+        types: None,
+        numbers: None,
+    }
+}
+
+/// A sheet cell in the text form the analysis types columns from: booleans as
+/// `true`/`false`, dates as `YYYY-MM-DD`, durations in seconds and error cells as missing
+fn sheet_cell(cell: &calamine::Data) -> String {
+    use calamine::Data;
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.clone(),
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) => f.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) if dt.is_duration() => dt
+            .as_duration()
+            .map_or_else(|| dt.as_f64().to_string(), |d| d.num_seconds().to_string()),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) if d.time() == chrono::NaiveTime::MIN => d.format("%Y-%m-%d").to_string(),
+            Some(d) => d.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => dt.as_f64().to_string(),
+        },
+    }
+}
+
+/// Align JSON records on the union of their keys; missing keys and nulls become empty cells
+fn objects_to_dataset(objects: &[&serde_json::Map<String, serde_json::Value>]) -> Dataset {
+    // Columns in first-seen order across all records
+    let mut headers: Vec<String> = Vec::new();
+    for obj in objects {
+        for key in obj.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = objects
+        .iter()
+        .map(|obj| {
+            headers
+                .iter()
+                .map(|h| match obj.get(h) {
+                    None | Some(serde_json::Value::Null) => String::new(),
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(v) => v.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    Dataset {
+        headers,
+        rows,
+        sample: None,
+        types: None,
+        numbers: None,
+    }
+}
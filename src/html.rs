@@ -0,0 +1,18 @@
+// HTML escaping for markup built outside templates (inline SVG charts), so
+// that headers and cell values can never inject markup into a report.
+
+/// Escape text for use in HTML element content and quoted attribute values
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,319 @@
+// Interactive single-file HTML report: table of contents, sortable overview table,
+// collapsible column sections, SVG charts with tooltips and zoom, dark/light theme.
+// Everything (CSS, JS, charts) is inlined so the report works offline.
+use crate::analyze::{ColumnProfile, Profile, median, quartiles};
+use crate::html::escape;
+use crate::report::{ChartOptions, HtmlOptions};
+use crate::templates::{ChartContext, TemplateKind, build_context, load_template, render};
+use std::collections::HashMap;
+use std::io::Write;
+
+const STYLE: &str = r#"
+:root{--bg:#ffffff;--fg:#1d2430;--muted:#5b6575;--panel:#f4f6f9;--border:#d9dee6;--accent:#2f6fde;--accent2:#e0533d;--bar:#4c86e8}
+[data-theme=dark]{--bg:#12161c;--fg:#e3e7ee;--muted:#9aa4b4;--panel:#1b2029;--border:#2f3744;--accent:#6ea2ff;--accent2:#ff7a66;--bar:#5b93f0}
+*{box-sizing:border-box}
+body{margin:0;background:var(--bg);color:var(--fg);font:15px/1.5 system-ui,-apple-system,"Segoe UI",Roboto,sans-serif}
+header{position:sticky;top:0;z-index:2;display:flex;align-items:center;justify-content:space-between;padding:.6em 1.5em;background:var(--panel);border-bottom:1px solid var(--border)}
+header h1{font-size:1.2em;margin:0}
+header .file{color:var(--muted);font-size:.9em}
+button{background:var(--bg);color:var(--fg);border:1px solid var(--border);border-radius:6px;padding:.3em .8em;cursor:pointer;font:inherit}
+button:hover{border-color:var(--accent)}
+.layout{display:flex;align-items:flex-start}
+nav{position:sticky;top:3.5em;flex:0 0 220px;max-height:calc(100vh - 4em);overflow:auto;padding:1em;border-right:1px solid var(--border)}
+nav ul{list-style:none;margin:0;padding:0}
+nav li{margin:.15em 0}
+nav a{color:var(--fg);text-decoration:none}
+nav a:hover{color:var(--accent)}
+nav .sub{padding-left:1em;font-size:.9em}
+main{flex:1;min-width:0;padding:1em 2em 3em}
+h2{border-bottom:1px solid var(--border);padding-bottom:.2em}
+.toolbar{display:flex;gap:.5em;align-items:center;margin:.5em 0}
+.toolbar input{flex:1;max-width:320px;padding:.35em .6em;border:1px solid var(--border);border-radius:6px;background:var(--bg);color:var(--fg);font:inherit}
+table{border-collapse:collapse;width:100%;font-size:.92em}
+th,td{border-bottom:1px solid var(--border);padding:.35em .6em;text-align:left}
+th{cursor:pointer;user-select:none;background:var(--panel);white-space:nowrap}
+th[data-dir=asc]::after{content:" \25B2"}
+th[data-dir=desc]::after{content:" \25BC"}
+td.num{text-align:right;font-variant-numeric:tabular-nums}
+details{border:1px solid var(--border);border-radius:8px;margin:1em 0;background:var(--panel)}
+details>summary{cursor:pointer;padding:.6em 1em;font-weight:600}
+details>.body{padding:0 1em 1em;background:var(--bg);border-radius:0 0 8px 8px}
+.badge{display:inline-block;font-size:.75em;font-weight:500;padding:.05em .5em;margin-left:.5em;border-radius:999px;border:1px solid var(--border);color:var(--muted)}
+.stats{display:grid;grid-template-columns:repeat(auto-fill,minmax(140px,1fr));gap:.5em;margin:1em 0}
+.stat{background:var(--panel);border-radius:6px;padding:.4em .7em}
+.stat .k{color:var(--muted);font-size:.8em}
+.stat .v{font-weight:600}
+.charts{display:flex;flex-wrap:wrap;gap:1em}
+svg.chart{max-width:100%;height:auto;border:1px solid var(--border);border-radius:6px;background:var(--bg);cursor:zoom-in}
+svg.chart text{fill:var(--muted);font-size:11px}
+svg.chart .axis{stroke:var(--border)}
+svg.chart .bar{fill:var(--bar)}
+svg.chart .box{fill:var(--accent);fill-opacity:.35;stroke:var(--accent)}
+svg.chart .line{stroke:var(--fg);stroke-width:2}
+svg.chart .outlier{fill:var(--accent2)}
+svg.chart [data-tip]:hover{opacity:.75}
+.hint{color:var(--muted);font-size:.8em}
+#tooltip{position:absolute;z-index:3;pointer-events:none;background:var(--fg);color:var(--bg);padding:.2em .5em;border-radius:4px;font-size:.85em;white-space:nowrap}
+"#;
+
+/// Default interactive report template
+pub const DEFAULT_INTERACTIVE_TEMPLATE: &str = include_str!("../templates/interactive.html");
+
+/// Generate a polished, interactive HTML report that works offline. The
+/// statistics come from `profile`; charts are drawn from the columns it was
+/// computed from, as inline SVG of the `options.chart` size whatever its
+/// format. `options.template` replaces the built-in template.
+pub fn generate_interactive_html_report(
+    profile: &Profile,
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    report_name: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let template = match &options.template {
+        Some(path) => load_template(Some(path), TemplateKind::Html)?,
+        None => DEFAULT_INTERACTIVE_TEMPLATE.to_string(),
+    };
+    let mut context = build_context(profile);
+    context.css = STYLE.to_string();
+    if options.charts {
+        for column in &mut context.columns {
+            column.charts = inline_charts(&column.profile, num_columns, cat_columns, &options.chart);
+        }
+    }
+    let html = render(&template, TemplateKind::Html, &context)?;
+    let mut file = crate::report::create_report_file(report_name)?;
+    file.write_all(html.as_bytes())?;
+    Ok(())
+}
+
+/// The inline SVG charts of one column part
+fn inline_charts(
+    column: &ColumnProfile,
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    chart: &ChartOptions,
+) -> Vec<ChartContext> {
+    let (i, name) = (column.index, column.name.as_str());
+    let charts = if column.numeric.is_some() {
+        vec![
+            ("histogram", "Histogram", svg_histogram(&num_columns[i], name, chart)),
+            ("boxplot", "Boxplot", svg_boxplot(&num_columns[i], name, chart)),
+        ]
+    } else {
+        vec![("bar", "Bar Chart", svg_bar_chart(&cat_columns[i], name, chart))]
+    };
+    charts
+        .into_iter()
+        .map(|(kind, title, svg)| ChartContext {
+            kind: kind.to_string(),
+            alt: format!("{} {}", title, name),
+            src: None,
+            svg: Some(svg),
+        })
+        .collect()
+}
+
+// Chart margins shared by the inline SVG charts; their size comes from the
+// chart options
+const LEFT: f64 = 48.0;
+const RIGHT: f64 = 16.0;
+const TOP: f64 = 28.0;
+const BOTTOM: f64 = 36.0;
+
+/// Width and height of a chart, in SVG user units
+fn size(chart: &ChartOptions) -> (f64, f64) {
+    (chart.width as f64, chart.height as f64)
+}
+
+fn svg_open(title: &str, chart: &ChartOptions) -> String {
+    let (width, height) = size(chart);
+    format!(
+        "<svg class='chart' width='{w}' height='{h}' viewBox='0 0 {w} {h}' xmlns='http://www.w3.org/2000/svg' role='img' aria-label='{t}'><text x='{x}' y='18' text-anchor='middle'>{t}</text><line class='axis' x1='{l}' y1='{b}' x2='{r}' y2='{b}'/><line class='axis' x1='{l}' y1='{tp}' x2='{l}' y2='{b}'/>",
+        w = width,
+        h = height,
+        t = escape(title),
+        x = width / 2.0,
+        l = LEFT,
+        r = width - RIGHT,
+        tp = TOP,
+        b = height - BOTTOM,
+    )
+}
+
+/// Inline SVG histogram with one tooltip per bin
+fn svg_histogram(data: &[f64], colname: &str, chart: &ChartOptions) -> String {
+    let (width, height) = size(chart);
+    let min = data.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let bin_count = 20;
+    let bin_width = (max - min) / bin_count as f64;
+    let mut bins = vec![0usize; bin_count];
+    for &v in data {
+        let idx = if bin_width > 0.0 {
+            ((v - min) / bin_width).floor() as usize
+        } else {
+            0
+        };
+        bins[idx.min(bin_count - 1)] += 1;
+    }
+    let max_count = bins.iter().cloned().max().unwrap_or(0).max(1);
+    let plot_w = width - LEFT - RIGHT;
+    let plot_h = height - TOP - BOTTOM;
+    let slot = plot_w / bin_count as f64;
+    let mut svg = svg_open(&format!("Histogram - {}", colname), chart);
+    for (i, &count) in bins.iter().enumerate() {
+        let h = count as f64 / max_count as f64 * plot_h;
+        let x0 = min + i as f64 * bin_width;
+        svg.push_str(&format!(
+            "<rect class='bar' x='{:.1}' y='{:.1}' width='{:.1}' height='{:.1}' data-tip='[{:.2}, {:.2}): {}'/>",
+            LEFT + i as f64 * slot + 1.0,
+            TOP + plot_h - h,
+            (slot - 2.0).max(1.0),
+            h,
+            x0,
+            x0 + bin_width,
+            count
+        ));
+    }
+    svg.push_str(&axis_labels(&format!("{:.2}", min), &format!("{:.2}", max), max_count, chart));
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Inline SVG horizontal boxplot with tooltips on the box, whiskers and outliers
+fn svg_boxplot(data: &[f64], colname: &str, chart: &ChartOptions) -> String {
+    let (width, height) = size(chart);
+    let min = data.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let (q1, q3) = quartiles(data);
+    let med = median(data);
+    let iqr = q3 - q1;
+    let lower_whisker = data
+        .iter()
+        .cloned()
+        .filter(|v| *v >= (q1 - 1.5 * iqr))
+        .fold(q1, f64::min);
+    let upper_whisker = data
+        .iter()
+        .cloned()
+        .filter(|v| *v <= (q3 + 1.5 * iqr))
+        .fold(q3, f64::max);
+    let plot_w = width - LEFT - RIGHT;
+    let span = if max > min { max - min } else { 1.0 };
+    let x = |v: f64| LEFT + (v - min) / span * plot_w;
+    let mid = TOP + (height - TOP - BOTTOM) / 2.0;
+    let mut svg = svg_open(&format!("Boxplot - {}", colname), chart);
+    svg.push_str(&format!(
+        "<line class='line' x1='{:.1}' y1='{mid:.1}' x2='{:.1}' y2='{mid:.1}'/>",
+        x(lower_whisker),
+        x(q1),
+        mid = mid
+    ));
+    svg.push_str(&format!(
+        "<line class='line' x1='{:.1}' y1='{mid:.1}' x2='{:.1}' y2='{mid:.1}'/>",
+        x(q3),
+        x(upper_whisker),
+        mid = mid
+    ));
+    for (v, label) in [(lower_whisker, "Lower whisker"), (upper_whisker, "Upper whisker")] {
+        svg.push_str(&format!(
+            "<line class='line' x1='{x:.1}' y1='{:.1}' x2='{x:.1}' y2='{:.1}' data-tip='{}: {:.2}'/>",
+            mid - 20.0,
+            mid + 20.0,
+            label,
+            v,
+            x = x(v)
+        ));
+    }
+    svg.push_str(&format!(
+        "<rect class='box' x='{:.1}' y='{:.1}' width='{:.1}' height='80' data-tip='Q1: {:.2}, Q3: {:.2}, IQR: {:.2}'/>",
+        x(q1),
+        mid - 40.0,
+        (x(q3) - x(q1)).max(1.0),
+        q1,
+        q3,
+        iqr
+    ));
+    svg.push_str(&format!(
+        "<line class='line' x1='{x:.1}' y1='{:.1}' x2='{x:.1}' y2='{:.1}' data-tip='Median: {:.2}'/>",
+        mid - 40.0,
+        mid + 40.0,
+        med,
+        x = x(med)
+    ));
+    for v in data.iter().filter(|v| **v < lower_whisker || **v > upper_whisker) {
+        svg.push_str(&format!(
+            "<circle class='outlier' cx='{:.1}' cy='{:.1}' r='4' data-tip='Outlier: {}'/>",
+            x(*v),
+            mid,
+            v
+        ));
+    }
+    svg.push_str(&format!(
+        "<text x='{}' y='{}' text-anchor='start'>{:.2}</text><text x='{}' y='{}' text-anchor='end'>{:.2}</text>",
+        LEFT,
+        height - BOTTOM + 16.0,
+        min,
+        width - RIGHT,
+        height - BOTTOM + 16.0,
+        max
+    ));
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Inline SVG bar chart of the most frequent values, largest first
+fn svg_bar_chart(data: &[String], colname: &str, chart: &ChartOptions) -> String {
+    let (width, height) = size(chart);
+    const MAX_BARS: usize = 30;
+    let mut freq_map: HashMap<&String, usize> = HashMap::new();
+    for val in data {
+        *freq_map.entry(val).or_insert(0) += 1;
+    }
+    let mut freq: Vec<_> = freq_map.into_iter().collect();
+    freq.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let hidden = freq.len().saturating_sub(MAX_BARS);
+    freq.truncate(MAX_BARS);
+    let max_count = freq.first().map(|f| f.1).unwrap_or(0).max(1);
+    let plot_w = width - LEFT - RIGHT;
+    let plot_h = height - TOP - BOTTOM;
+    let slot = plot_w / freq.len().max(1) as f64;
+    let title = if hidden > 0 {
+        format!("Bar Chart - {} (top {} of {})", colname, MAX_BARS, MAX_BARS + hidden)
+    } else {
+        format!("Bar Chart - {}", colname)
+    };
+    let mut svg = svg_open(&title, chart);
+    for (i, (val, count)) in freq.iter().enumerate() {
+        let h = *count as f64 / max_count as f64 * plot_h;
+        svg.push_str(&format!(
+            "<rect class='bar' x='{:.1}' y='{:.1}' width='{:.1}' height='{:.1}' data-tip='{}: {}'/>",
+            LEFT + i as f64 * slot + 1.0,
+            TOP + plot_h - h,
+            (slot - 2.0).max(1.0),
+            h,
+            escape(val),
+            count
+        ));
+    }
+    svg.push_str(&axis_labels("", "", max_count, chart));
+    svg.push_str("</svg>");
+    svg
+}
+
+fn axis_labels(x_min: &str, x_max: &str, y_max: usize, chart: &ChartOptions) -> String {
+    let (width, height) = size(chart);
+    format!(
+        "<text x='{l}' y='{yb}' text-anchor='start'>{}</text><text x='{r}' y='{yb}' text-anchor='end'>{}</text><text x='{yl}' y='{t}' text-anchor='end'>{}</text><text x='{yl}' y='{b}' text-anchor='end'>0</text>",
+        x_min,
+        x_max,
+        y_max,
+        l = LEFT,
+        r = width - RIGHT,
+        yb = height - BOTTOM + 16.0,
+        yl = LEFT - 6.0,
+        t = TOP + 4.0,
+        b = height - BOTTOM,
+    )
+}
@@ -1,3 +1,18 @@
 pub mod analyze;
+pub mod analyzer;
+pub mod avro;
+pub mod compression;
+pub mod diff;
+pub mod drift;
+pub mod error;
 pub mod formats;
+pub mod html;
+pub mod interactive;
+pub mod profile;
+pub mod python;
+pub mod renderer;
 pub mod report;
+pub mod sample;
+pub mod sniff;
+pub mod templates;
+pub mod validate;
@@ -1,152 +1,412 @@
-#![allow(deprecated, unsafe_op_in_unsafe_fn)]
 #![allow(dead_code)]
-use pyo3::prelude::*;
 use std::env;
+use std::process::ExitCode;
 mod analyze;
+mod analyzer;
+mod avro;
+mod cli;
+mod compression;
+mod diff;
+mod drift;
+mod error;
 mod formats;
+mod html;
+mod interactive;
+mod profile;
+mod renderer;
 mod report;
+mod sample;
+mod sniff;
+mod templates;
+mod validate;
+use clap::{CommandFactory, Parser};
+use cli::{Cli, Command, Format};
+use formats::Dataset;
 use std::error::Error;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <data_file> [output_report.html]", args[0]);
-        std::process::exit(1);
+/// Status lines go to stdout unless `--quiet`, or to stderr while the report
+/// itself is written to stdout; details only with `--verbose`
+#[derive(Clone, Copy)]
+struct Output {
+    verbose: bool,
+    quiet: bool,
+    status_to_stderr: bool,
+}
+
+impl Output {
+    /// The output to use while writing `report_name`
+    fn for_report(&self, report_name: &str) -> Output {
+        Output {
+            status_to_stderr: report_name == report::STDOUT,
+            ..*self
+        }
     }
-    let filename = &args[1];
-    let report_name = if args.len() > 2 {
-        &args[2]
-    } else {
-        "rapport.html"
-    };
-    if let Err(e) = analyze_csv_with_report(filename, report_name) {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+
+    fn status(&self, message: impl std::fmt::Display) {
+        if self.quiet {
+            return;
+        }
+        if self.status_to_stderr {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    fn generated(&self, kind: &str, report_name: &str) {
+        if report_name == report::STDOUT {
+            self.detail(format!("{} written to standard output", kind));
+        } else {
+            self.status(format!("{} generated: {}", kind, report_name));
+        }
     }
 
-    #[pyfunction]
-    fn analyze_csv_py(path: &str) -> PyResult<String> {
-        match analyze_csv_with_report(path, "rapport.html") {
-            Ok(_) => Ok("Report generated successfully".to_string()),
-            Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(e.to_string())),
+    fn detail(&self, message: impl std::fmt::Display) {
+        if self.verbose {
+            eprintln!("{}", message);
         }
     }
+}
+
+/// Read a data file and apply the column selection and row sample
+fn load_dataset(
+    path: &str,
+    input: &cli::InputArgs,
+    out: &Output,
+) -> Result<Dataset, Box<dyn Error>> {
+    let dataset = formats::read_dataset_with_options(path, &input.read_options())?;
+    out.detail(format!(
+        "Read {} rows and {} columns from {}",
+        dataset.rows.len(),
+        dataset.headers.len(),
+        path
+    ));
+    prepare_dataset(dataset, &input.include, &input.exclude, out)
+}
 
-    #[pymodule]
-    fn datastory(_py: Python, m: &PyModule) -> PyResult<()> {
-        m.add_function(wrap_pyfunction!(analyze_csv_py, m)?)?;
-        Ok(())
-    }
-    fn analyze_csv_with_report(path: &str, report_name: &str) -> Result<(), Box<dyn Error>> {
-        let ext = std::path::Path::new(path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-        let data = match ext {
-            "csv" => formats::read_csv(path)?,
-            "parquet" => formats::read_parquet(path)?,
-            "json" => formats::read_json(path)?,
-            _ => return Err("Unsupported file format".into()),
+/// Read every sheet of a workbook (a single unnamed sheet for other files) and
+/// apply the column selection and row sample to each. Selected columns only
+/// need to exist in one of the sheets.
+fn load_sheets(
+    path: &str,
+    input: &cli::InputArgs,
+    out: &Output,
+) -> Result<Vec<formats::Sheet>, Box<dyn Error>> {
+    let mut sheets = formats::read_sheets(path, &input.read_options())?;
+    if sheets.len() == 1 {
+        let sheet = sheets.remove(0);
+        out.detail(format!(
+            "Read {} rows and {} columns from {}",
+            sheet.dataset.rows.len(),
+            sheet.dataset.headers.len(),
+            path
+        ));
+        let dataset =
+            prepare_dataset(sheet.dataset, &input.include, &input.exclude, out)?;
+        return Ok(vec![formats::Sheet {
+            name: sheet.name,
+            dataset,
+        }]);
+    }
+    for name in input.include.iter().chain(&input.exclude) {
+        if !sheets.iter().any(|s| s.dataset.headers.contains(name)) {
+            return Err(format!("Unknown column '{}'", name).into());
+        }
+    }
+    let mut selected = Vec::new();
+    for sheet in sheets {
+        out.detail(format!(
+            "Read {} rows and {} columns from sheet '{}' of {}",
+            sheet.dataset.rows.len(),
+            sheet.dataset.headers.len(),
+            sheet.name,
+            path
+        ));
+        let present = |names: &[String]| -> Vec<String> {
+            names
+                .iter()
+                .filter(|n| sheet.dataset.headers.contains(n))
+                .cloned()
+                .collect()
         };
-        // Split numeric and categorical columns
-        let col_count = if !data.is_empty() { data[0].len() } else { 0 };
-        let mut num_columns: Vec<Vec<f64>> = vec![vec![]; col_count];
-        let mut cat_columns: Vec<Vec<String>> = vec![vec![]; col_count];
-        for row in &data {
-            for (i, value) in row.iter().enumerate() {
-                if let Ok(num) = value.parse::<f64>() {
-                    num_columns[i].push(num);
-                } else {
-                    cat_columns[i].push(value.to_string());
-                }
-            }
+        let include = present(&input.include);
+        if !input.include.is_empty() && include.is_empty() {
+            continue;
         }
-        let headers: Vec<String> = (0..col_count)
-            .map(|i| format!("Column_{}", i + 1))
-            .collect();
+        let exclude = present(&input.exclude);
+        let dataset = prepare_dataset(sheet.dataset, &include, &exclude, out)?;
+        selected.push(formats::Sheet { name: sheet.name, dataset });
+    }
+    Ok(selected)
+}
 
-        // Generate HTML report
-        report::generate_html_report(
-            &num_columns,
-            &cat_columns,
-            &headers,
-            path,
-            report_name,
-        )?;
-        println!("HTML report generated: {}", report_name);
-        Ok(())
-    }
-
-    fn save_histogram(
-        filename: &str,
-        data: &[f64],
-        colname: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        use plotters::prelude::*;
-        let root = BitMapBackend::new(filename, (640, 480)).into_drawing_area();
-        root.fill(&WHITE)?;
-        let min = data.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-        let mut chart = ChartBuilder::on(&root)
-            .caption(format!("Histogram - {}", colname), ("sans-serif", 30))
-            .margin(20)
-            .x_label_area_size(40)
-            .y_label_area_size(40)
-            .build_cartesian_2d(min..max, 0..data.len() as u32)?;
-        chart.configure_mesh().draw()?;
-        let bin_count = 20;
-        let bin_width = (max - min) / bin_count as f64;
-        let mut bins = vec![0u32; bin_count];
-        for &v in data {
-            let idx = ((v - min) / bin_width).floor() as usize;
-            let idx = if idx >= bin_count { bin_count - 1 } else { idx };
-            bins[idx] += 1;
+fn prepare_dataset(
+    mut dataset: Dataset,
+    include: &[String],
+    exclude: &[String],
+    out: &Output,
+) -> Result<Dataset, Box<dyn Error>> {
+    if let Some(sample) = &dataset.sample {
+        out.detail(sample.description());
+    }
+    if !include.is_empty() || !exclude.is_empty() {
+        dataset = dataset.select_columns(include, exclude)?;
+        out.detail(format!("Selected columns: {}", dataset.headers.join(", ")));
+    }
+    Ok(dataset)
+}
+
+/// Report file name: the one given, else standard output for a Markdown or
+/// JSON report on standard input, else `<stem>.<format>`
+fn report_name(
+    output: Option<&str>,
+    inputs: &[&str],
+    format: Format,
+    stem: &str,
+    output_args: &cli::OutputArgs,
+) -> Result<String, Box<dyn Error>> {
+    let stdout = format != Format::Html && inputs.contains(&compression::STDIN);
+    report_file_name(output, stdout, &format.default_file_name(stem), output_args)
+}
+
+/// The report file given, else standard output when `stdout`, else `default`,
+/// placed in `--output-dir`
+fn report_file_name(
+    output: Option<&str>,
+    stdout: bool,
+    default: &str,
+    output_args: &cli::OutputArgs,
+) -> Result<String, Box<dyn Error>> {
+    let name = match output {
+        Some(name) => name,
+        None if stdout => report::STDOUT,
+        None => default,
+    };
+    output_path(output_args, name)
+}
+
+/// Place the report inside `--output-dir` when one is given, creating the directory
+fn output_path(output: &cli::OutputArgs, name: &str) -> Result<String, Box<dyn Error>> {
+    match &output.output_dir {
+        _ if name == report::STDOUT => Ok(name.to_string()),
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            Ok(dir.join(name).to_string_lossy().into_owned())
         }
-        chart.draw_series(bins.iter().enumerate().map(|(i, &count)| {
-            let x0 = min + i as f64 * bin_width;
-            let x1 = x0 + bin_width;
-            Rectangle::new([(x0, 0), (x1, count)], BLUE.filled())
-        }))?;
-        Ok(())
-    }
-
-    fn quartiles(data: &[f64]) -> (f64, f64) {
-        let mut sorted = data.to_vec();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let len = sorted.len();
-        let q1_idx = len as f64 * 0.25;
-        let q3_idx = len as f64 * 0.75;
-        let q1 = sorted[q1_idx.floor() as usize];
-        let q3 = sorted[q3_idx.floor() as usize];
-        (q1, q3)
-    }
-
-    fn mode(data: &[String]) -> String {
-        use std::collections::HashMap;
-        let mut counts = HashMap::new();
-        for val in data {
-            *counts.entry(val).or_insert(0) += 1;
+        None => Ok(name.to_string()),
+    }
+}
+
+fn html_options(charts: &cli::ChartArgs, template: Option<String>) -> report::HtmlOptions {
+    report::HtmlOptions {
+        self_contained: charts.self_contained,
+        assets_dir: charts.assets_dir.clone().unwrap_or_else(|| report::HtmlOptions::default().assets_dir),
+        template,
+        charts: !charts.no_charts,
+        chart: charts.chart_options(),
+    }
+}
+
+/// `datastory report <data> [output]`: report of one file, in the format of
+/// any registered renderer
+fn run_report(args: &cli::ReportArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let format = args.renderer_name();
+    let renderer = renderer::renderer(format)
+        .ok_or_else(|| format!("Unsupported report format '{}'", format))?;
+    let report_name = report_file_name(
+        args.output.as_deref(),
+        renderer.stdout_by_default() && args.input == compression::STDIN,
+        &format!("rapport.{}", renderer.extension()),
+        &args.output_args,
+    )?;
+    let out = &out.for_report(&report_name);
+    let options = html_options(&args.charts, args.template.clone());
+    // Every sheet of a workbook gets its own section in HTML and Markdown
+    // reports; the other formats report one sheet, which `--sheet` must name
+    let kind = match format {
+        "html" => Some(templates::TemplateKind::Html),
+        "md" => Some(templates::TemplateKind::Markdown),
+        _ => None,
+    };
+    let sheets = load_sheets(&args.input, &args.input_args, out)?;
+    match kind {
+        Some(kind) if sheets.len() > 1 => {
+            report::generate_sheets_report(&sheets, &args.input, &report_name, kind, &options)?
+        }
+        None if sheets.len() > 1 => {
+            return Err(format!(
+                "'{}' has {} sheets but a {} report holds one; pick it with --sheet",
+                args.input,
+                sheets.len(),
+                format
+            )
+            .into());
+        }
+        _ => {
+            let dataset = sheets.into_iter().next().map(|sheet| sheet.dataset).unwrap_or_default();
+            report::generate_report(&dataset, &args.input, &report_name, format, &options)?;
         }
-        counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(val, _)| val.clone())
-            .unwrap_or_default()
     }
+    out.generated(renderer.description(), &report_name);
+    Ok(cli::EXIT_OK)
+}
+
+/// `datastory profile <data> [output]`: the versioned JSON profile
+fn run_profile(args: &cli::ProfileArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let report_name = report_name(
+        args.output.as_deref(),
+        &[&args.input],
+        Format::Json,
+        "profile",
+        &args.output_args,
+    )?;
+    let out = &out.for_report(&report_name);
+    let dataset = load_dataset(&args.input, &args.input_args, out)?;
+    let options = report::HtmlOptions::default();
+    report::generate_report(&dataset, &args.input, &report_name, "json", &options)?;
+    out.generated("JSON profile", &report_name);
+    Ok(cli::EXIT_OK)
+}
+
+/// `datastory diff <old> <new> [output]`: compare two versions of a dataset
+fn run_diff(args: &cli::DiffArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let report_name = report_name(
+        args.output.as_deref(),
+        &[&args.old, &args.new],
+        args.format,
+        "diff",
+        &args.output_args,
+    )?;
+    let out = &out.for_report(&report_name);
+    let old = load_dataset(&args.old, &args.input_args, out)?;
+    let new = load_dataset(&args.new, &args.input_args, out)?;
+    let diff = diff::diff_datasets(&old, &new, &args.old, &args.new);
+    diff::generate_diff_report(&diff, &report_name, args.format.as_str())?;
+    out.generated("Diff report", &report_name);
+    Ok(cli::EXIT_OK)
+}
+
+/// `datastory drift <reference> <current> [output]`: distribution drift per column
+fn run_drift(args: &cli::DriftArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let report_name = report_name(
+        args.output.as_deref(),
+        &[&args.reference, &args.current],
+        args.format,
+        "drift",
+        &args.output_args,
+    )?;
+    let out = &out.for_report(&report_name);
+    let reference = load_dataset(&args.reference, &args.input_args, out)?;
+    let current = load_dataset(&args.current, &args.input_args, out)?;
+    let drift = drift::drift_datasets(&reference, &current, &args.reference, &args.current);
+    drift::generate_drift_report(
+        &drift,
+        &reference,
+        &current,
+        &report_name,
+        args.format.as_str(),
+        &html_options(&args.charts, None),
+    )?;
+    out.generated("Drift report", &report_name);
+    Ok(cli::EXIT_OK)
+}
 
-    fn mean(data: &[f64]) -> f64 {
-        let sum: f64 = data.iter().sum();
-        sum / (data.len() as f64)
+/// `datastory validate <data> <rules> [output]`: exits with 1 when a check fails
+fn run_validate(args: &cli::ValidateArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let rules = validate::load_rules(&args.rules)?;
+    let report_name = report_name(
+        args.output.as_deref(),
+        &[&args.input],
+        args.format,
+        "validation",
+        &args.output_args,
+    )?;
+    let out = &out.for_report(&report_name);
+    let dataset = load_dataset(&args.input, &args.input_args, out)?;
+    let report = validate::validate_dataset(&dataset, &rules, &args.input)?;
+    validate::generate_validation_report(&report, &report_name, args.format.as_str())?;
+    out.generated("Validation report", &report_name);
+    if report.passed {
+        out.status(format!("Validation passed: {} checks", report.checks.len()));
+        Ok(cli::EXIT_OK)
+    } else {
+        eprintln!(
+            "Validation failed: {} of {} checks failed",
+            report.failed_checks(),
+            report.checks.len()
+        );
+        Ok(cli::EXIT_CHECKS_FAILED)
     }
+}
 
-    fn median(data: &[f64]) -> f64 {
-        let mut sorted = data.to_vec();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let len = sorted.len();
-        if len.is_multiple_of(2) {
-            (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0
-        } else {
-            sorted[len / 2]
+/// `datastory clean <data>`: cleaning suggestions per column, printed to stdout
+fn run_clean(args: &cli::CleanArgs, out: &Output) -> Result<u8, Box<dyn Error>> {
+    let dataset = load_dataset(&args.input, &args.input_args, out)?;
+    let suggestions: Vec<(String, Vec<String>)> = dataset
+        .headers
+        .iter()
+        .map(|name| {
+            let values = dataset.column(name).unwrap_or_default();
+            (name.clone(), analyze::column_cleaning_suggestions(&values))
+        })
+        .collect();
+    if args.json {
+        let map: serde_json::Map<String, serde_json::Value> = suggestions
+            .into_iter()
+            .map(|(name, s)| (name, serde_json::Value::from(s)))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&map)?);
+    } else {
+        for (name, list) in suggestions {
+            println!("{}:", name);
+            for s in list {
+                println!("  - {}", s);
+            }
+        }
+    }
+    Ok(cli::EXIT_OK)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse_from(cli::normalize_args(env::args().collect()));
+    if let Err(message) = cli.command.input_args().sampling() {
+        Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, message)
+            .exit();
+    }
+    if let Command::Report(args) = &cli.command
+        && let Err(message) = args.check_chart_options()
+    {
+        Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, message)
+            .exit();
+    }
+    let out = Output {
+        verbose: cli.verbose,
+        quiet: cli.quiet,
+        status_to_stderr: false,
+    };
+    if let Some(threads) = cli.threads
+        && let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+    {
+        eprintln!("Error: {}", e);
+        return ExitCode::from(cli::EXIT_ERROR);
+    }
+    let result = match &cli.command {
+        Command::Report(args) => run_report(args, &out),
+        Command::Profile(args) => run_profile(args, &out),
+        Command::Validate(args) => run_validate(args, &out),
+        Command::Diff(args) => run_diff(args, &out),
+        Command::Drift(args) => run_drift(args, &out),
+        Command::Clean(args) => run_clean(args, &out),
+    };
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(cli::EXIT_ERROR)
         }
     }
 }
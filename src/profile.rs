@@ -0,0 +1,57 @@
+// Machine-readable JSON profile: the whole analysis as a versioned document
+// described by schema/profile.schema.json.
+use crate::analyze::{Profile, profile_columns};
+use std::io::Write;
+
+/// Version of the profile document layout; bumped on incompatible changes
+pub const PROFILE_SCHEMA_VERSION: u32 = 1;
+/// JSON Schema describing the profile document
+pub const PROFILE_SCHEMA: &str = include_str!("../schema/profile.schema.json");
+
+/// Build the profile document for split numeric / categorical columns
+pub fn build_profile(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    path: &str,
+) -> Profile {
+    profile_columns(num_columns, cat_columns, headers, None, None).with_source(path)
+}
+
+/// Write the profile as pretty-printed JSON
+pub fn generate_json_profile(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    path: &str,
+    report_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_json_profile(&build_profile(num_columns, cat_columns, headers, path), report_name)
+}
+
+/// Write a profile document as pretty-printed JSON
+pub fn write_json_profile(
+    profile: &Profile,
+    report_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = crate::report::create_report_file(report_name)?;
+    serde_json::to_writer_pretty(&mut file, profile)?;
+    file.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Read back a profile written by [`write_json_profile`], e.g. to render
+/// reports from a cached analysis
+pub fn read_json_profile(path: &str) -> Result<Profile, Box<dyn std::error::Error>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("Cannot read '{}': {}", path, e)))?;
+    let profile: Profile = serde_json::from_reader(std::io::BufReader::new(file))?;
+    if profile.schema_version != PROFILE_SCHEMA_VERSION {
+        return Err(format!(
+            "'{}' is a version {} profile, expected version {}",
+            path, profile.schema_version, PROFILE_SCHEMA_VERSION
+        )
+        .into());
+    }
+    Ok(profile)
+}
@@ -0,0 +1,921 @@
+// Python bindings, built into the `datastory` extension module from the library
+// crate: profile and report on data files and DataFrames, `Report` objects that
+// render inline in Jupyter, and the column statistics on plain Python sequences.
+// Reading, analysis and rendering run with the GIL released, so other Python
+// threads and the asyncio event loop keep going meanwhile.
+// The pyo3 0.21 macros expand to unsafe calls inside unsafe functions.
+#![allow(unsafe_op_in_unsafe_fn)]
+use crate::analyze::{self, ColumnProfile, Profile, ReportSection};
+use crate::analyzer::{self, Analyzer, ColumnValues, DatasetColumns, TypedColumn};
+use crate::error::DataError;
+use crate::formats::{self, CsvOptions, Dataset, ReadOptions};
+use crate::report::{self, HtmlOptions};
+use crate::sample::Sampling;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyCapsule, PyDict, PyList, PyTuple};
+use rayon::prelude::*;
+
+create_exception!(
+    datastory,
+    DatastoryError,
+    PyRuntimeError,
+    "Base class of the errors raised by datastory"
+);
+create_exception!(
+    datastory,
+    UnsupportedFormatError,
+    DatastoryError,
+    "The input or report format is not one datastory reads or writes"
+);
+create_exception!(
+    datastory,
+    ParseError,
+    DatastoryError,
+    "The input does not parse as the format it was read as"
+);
+
+#[pymodule]
+fn datastory(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("DatastoryError", py.get_type_bound::<DatastoryError>())?;
+    m.add("UnsupportedFormatError", py.get_type_bound::<UnsupportedFormatError>())?;
+    m.add("ParseError", py.get_type_bound::<ParseError>())?;
+    m.add_class::<Report>()?;
+    m.add_function(wrap_pyfunction!(analyze_source, m)?)?;
+    m.add_function(wrap_pyfunction!(profile, m)?)?;
+    m.add_function(wrap_pyfunction!(write_report, m)?)?;
+    m.add_function(wrap_pyfunction!(profile_many, m)?)?;
+    m.add_function(wrap_pyfunction!(profile_dataframe, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_async, m)?)?;
+    m.add_function(wrap_pyfunction!(profile_async, m)?)?;
+    m.add_function(wrap_pyfunction!(report_async, m)?)?;
+    m.add_function(wrap_pyfunction!(register_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(analyzers, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_csv_py, m)?)?;
+    m.add_function(wrap_pyfunction!(mean, m)?)?;
+    m.add_function(wrap_pyfunction!(median, m)?)?;
+    m.add_function(wrap_pyfunction!(mode, m)?)?;
+    m.add_function(wrap_pyfunction!(quartiles, m)?)?;
+    m.add_function(wrap_pyfunction!(pearson_correlation, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_column_type, m)?)?;
+    m.add_function(wrap_pyfunction!(cleaning_suggestions, m)?)?;
+    Ok(())
+}
+
+/// Reader options, column selection and worker threads given as keyword arguments
+#[derive(Default)]
+struct InputOptions {
+    read: ReadOptions,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    /// Worker threads for the analysis and charts; one per CPU when unset
+    threads: Option<usize>,
+}
+
+impl InputOptions {
+    /// Parse the keyword arguments of `profile`, `report` and `analyze`; they
+    /// mirror the CLI input options and `--threads`, and unknown names are a
+    /// `TypeError`
+    fn from_kwargs(options: Option<&Bound<'_, PyDict>>) -> PyResult<InputOptions> {
+        let mut parsed = InputOptions::default();
+        let Some(options) = options else {
+            return Ok(parsed);
+        };
+        let csv = &mut parsed.read.csv;
+        let (mut method, mut rows, mut fraction, mut stratify_by, mut seed) =
+            (None, None, None, None, None);
+        for (key, value) in options.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "input_format" => {
+                    parsed.read.format = value
+                        .extract::<Option<String>>()?
+                        .map(|f| f.parse())
+                        .transpose()
+                        .map_err(UnsupportedFormatError::new_err)?
+                }
+                "delimiter" => csv.delimiter = ascii_option("delimiter", value.extract()?)?,
+                "quote" => csv.quote = ascii_option("quote", value.extract()?)?,
+                "escape" => csv.escape = ascii_option("escape", value.extract()?)?,
+                "has_header" => csv.has_headers = value.extract()?,
+                "skip_rows" => csv.skip_rows = value.extract()?,
+                "comment" => csv.comment = ascii_option("comment", value.extract()?)?,
+                "flexible" => csv.flexible = value.extract()?,
+                "encoding" => {
+                    csv.encoding = value
+                        .extract::<Option<String>>()?
+                        .map(|label| formats::parse_encoding(&label))
+                        .transpose()
+                        .map_err(PyValueError::new_err)?
+                }
+                "sheet" => {
+                    parsed.read.sheet = match value.extract::<Option<usize>>() {
+                        Ok(position) => position.map(|n| n.to_string()),
+                        Err(_) => value.extract()?,
+                    }
+                }
+                "include" => parsed.include = value.extract::<Option<_>>()?.unwrap_or_default(),
+                "exclude" => parsed.exclude = value.extract::<Option<_>>()?.unwrap_or_default(),
+                "sample" => rows = value.extract()?,
+                "sample_fraction" => fraction = value.extract()?,
+                "sample_method" => {
+                    method = value
+                        .extract::<Option<String>>()?
+                        .map(|m| m.parse())
+                        .transpose()
+                        .map_err(PyValueError::new_err)?
+                }
+                "stratify_by" => stratify_by = value.extract()?,
+                "seed" => seed = value.extract()?,
+                "threads" => parsed.threads = value.extract()?,
+                other => {
+                    return Err(PyTypeError::new_err(format!(
+                        "unexpected keyword argument '{}'",
+                        other
+                    )));
+                }
+            }
+        }
+        parsed.read.sample = Sampling::new(method, rows, fraction, stratify_by, seed)
+            .map_err(PyValueError::new_err)?;
+        Ok(parsed)
+    }
+
+    /// Read a data file, sampling its rows as it is read, and select the columns
+    fn load(&self, path: &str) -> Result<Dataset, Box<dyn std::error::Error>> {
+        self.select_columns(formats::read_dataset_with_options(path, &self.read)?)
+    }
+
+    /// Apply the row sample and the column selection
+    fn select(&self, dataset: Dataset) -> Result<Dataset, Box<dyn std::error::Error>> {
+        match &self.read.sample {
+            Some(sampling) => self.select_columns(sampling.apply(dataset)?),
+            None => self.select_columns(dataset),
+        }
+    }
+
+    fn select_columns(&self, dataset: Dataset) -> Result<Dataset, Box<dyn std::error::Error>> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return Ok(dataset);
+        }
+        dataset.select_columns(&self.include, &self.exclude)
+    }
+}
+
+/// Run `work` with the GIL released: on a scoped pool of `threads` worker
+/// threads when given, else on the global one (one thread per CPU)
+fn without_gil<T: Send>(
+    py: Python<'_>,
+    threads: Option<usize>,
+    work: impl FnOnce() -> Result<T, Failure> + Send,
+) -> PyResult<T> {
+    let pool = match threads {
+        None => None,
+        Some(0) => return Err(PyValueError::new_err("threads must be at least 1")),
+        Some(n) => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| DatastoryError::new_err(e.to_string()))?,
+        ),
+    };
+    let result = py.allow_threads(|| match &pool {
+        Some(pool) => pool.install(work),
+        None => work(),
+    });
+    Ok(result?)
+}
+
+/// Python characters for CSV options must be single ASCII bytes
+fn ascii_option(name: &str, value: Option<char>) -> PyResult<Option<u8>> {
+    value
+        .map(|c| {
+            u8::try_from(c).ok().filter(u8::is_ascii).ok_or_else(|| {
+                PyValueError::new_err(format!("{} must be a single ASCII character", name))
+            })
+        })
+        .transpose()
+}
+
+/// A failure carried out of code run without the GIL (boxed errors are not
+/// `Send`), sorted into the Python exception it raises
+#[derive(Debug)]
+enum Failure {
+    /// Raised as the matching `OSError` subclass, e.g. `FileNotFoundError`
+    Io(std::io::Error),
+    UnsupportedFormat(String),
+    Parse(String),
+    Other(String),
+}
+
+impl From<Box<dyn std::error::Error>> for Failure {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        if let Some(e) = e.downcast_ref::<DataError>() {
+            return match e.clone() {
+                DataError::UnsupportedFormat(message) => Failure::UnsupportedFormat(message),
+                DataError::Parse(message) => Failure::Parse(message),
+            };
+        }
+        let e = match e.downcast::<std::io::Error>() {
+            Ok(e) => return Failure::Io(*e),
+            Err(e) => e,
+        };
+        let parse = e.is::<csv::Error>()
+            || e.is::<serde_json::Error>()
+            || e.is::<parquet::errors::ParquetError>()
+            || e.is::<calamine::Error>()
+            || e.is::<arrow_schema::ArrowError>();
+        if parse {
+            Failure::Parse(e.to_string())
+        } else {
+            Failure::Other(e.to_string())
+        }
+    }
+}
+
+impl From<Failure> for PyErr {
+    fn from(failure: Failure) -> Self {
+        match failure {
+            Failure::Io(e) => e.into(),
+            Failure::UnsupportedFormat(message) => UnsupportedFormatError::new_err(message),
+            Failure::Parse(message) => ParseError::new_err(message),
+            Failure::Other(message) => DatastoryError::new_err(message),
+        }
+    }
+}
+
+/// An analysed dataset. In Jupyter it renders inline as the self-contained
+/// HTML report; `save` writes it as HTML, PDF, Markdown or JSON.
+#[pyclass(module = "datastory")]
+struct Report {
+    /// Computed once; every rendering reads its statistics from it, so they
+    /// all agree even if analyzers are registered in between
+    profile: Profile,
+    headers: Vec<String>,
+    /// Column values the charts are drawn from
+    num_columns: Vec<Vec<f64>>,
+    cat_columns: Vec<Vec<String>>,
+    /// Worker threads the report was analysed with, also used to render it
+    threads: Option<usize>,
+}
+
+impl Report {
+    /// Analyse a dataset; call without the GIL
+    fn new(dataset: Dataset, source: &str, threads: Option<usize>) -> Report {
+        let (num_columns, cat_columns) = dataset.split_columns();
+        let (types, sample) = (dataset.types.as_deref(), dataset.sample.as_ref());
+        let profile =
+            analyze::profile_columns(&num_columns, &cat_columns, &dataset.headers, types, sample)
+                .with_source(source);
+        Report {
+            profile,
+            headers: dataset.headers,
+            num_columns,
+            cat_columns,
+            threads,
+        }
+    }
+}
+
+#[pymethods]
+impl Report {
+    /// Where the data came from, as shown in the report
+    #[getter]
+    fn source(&self) -> &str {
+        &self.profile.source
+    }
+
+    #[getter]
+    fn row_count(&self) -> usize {
+        self.profile.row_count
+    }
+
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.headers.clone()
+    }
+
+    /// The self-contained HTML report: charts as inline SVG, styles included
+    fn to_html(&self, py: Python<'_>) -> PyResult<String> {
+        let options = HtmlOptions {
+            self_contained: true,
+            ..HtmlOptions::default()
+        };
+        let html = without_gil(py, self.threads, || {
+            report::render_html_report(&self.profile, &self.num_columns, &self.cat_columns, "", &options)
+                .map_err(Failure::from)
+        })?;
+        Ok(html)
+    }
+
+    /// The Markdown report, without charts (they are image files; see `save`)
+    fn to_markdown(&self, py: Python<'_>) -> PyResult<String> {
+        let options = HtmlOptions {
+            charts: false,
+            ..HtmlOptions::default()
+        };
+        let markdown = without_gil(py, self.threads, || {
+            report::render_markdown_report(&self.profile, &self.num_columns, &self.cat_columns, "", &options)
+                .map_err(Failure::from)
+        })?;
+        Ok(markdown)
+    }
+
+    /// The JSON profile document, as a dict
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let value = serde_json::to_value(&self.profile).map_err(|e| DatastoryError::new_err(e.to_string()))?;
+        json_to_py(py, &value)
+    }
+
+    /// Write the report to `path` and return it. The format is `html`, `pdf`,
+    /// `md` or `json`, taken from the file extension when not given. HTML is
+    /// self-contained; Markdown charts go to `assets/` next to the file.
+    #[pyo3(signature = (path, format = None))]
+    fn save(&self, py: Python<'_>, path: &str, format: Option<&str>) -> PyResult<String> {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        // Any registered renderer; unknown formats raise UnsupportedFormatError
+        let format = match format.unwrap_or(&extension) {
+            "htm" => "html",
+            other => other,
+        };
+        let options = HtmlOptions {
+            self_contained: true,
+            ..HtmlOptions::default()
+        };
+        without_gil(py, self.threads, || {
+            let (num, cat) = (&self.num_columns, &self.cat_columns);
+            report::render_report(&self.profile, num, cat, path, format, &options).map_err(Failure::from)
+        })?;
+        Ok(path.to_string())
+    }
+
+    /// Jupyter rich display: the HTML report in an iframe, so its styles do
+    /// not leak into the notebook
+    fn _repr_html_(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "<iframe srcdoc=\"{}\" style=\"width:100%;height:640px;border:1px solid #ddd\"></iframe>",
+            crate::html::escape(&self.to_html(py)?)
+        ))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<datastory.Report source={:?} rows={} columns={}>",
+            self.profile.source,
+            self.profile.row_count,
+            self.headers.len()
+        )
+    }
+}
+
+/// Analyse a data file (path) or a DataFrame and return a `Report`. Keyword
+/// options are those of `profile`; for DataFrames only `include`, `exclude`
+/// and the sampling options apply. `name` overrides the source shown in the report.
+#[pyfunction]
+#[pyo3(name = "analyze", signature = (source, name = None, **options))]
+fn analyze_source(
+    source: &Bound<'_, PyAny>,
+    name: Option<String>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Report> {
+    let input = InputOptions::from_kwargs(options)?;
+    let py = source.py();
+    let path_like = source.is_instance_of::<pyo3::types::PyString>()
+        || source.is_instance(&py.import_bound("os")?.getattr("PathLike")?)?;
+    let report = if path_like {
+        let path: std::path::PathBuf = source.extract()?;
+        let path = path.to_string_lossy().into_owned();
+        let name = name.unwrap_or_else(|| path.clone());
+        without_gil(py, input.threads, || Ok(Report::new(input.load(&path)?, &name, input.threads)))?
+    } else {
+        let dataset = dataframe_dataset(source)?;
+        let name = name.unwrap_or_else(|| "DataFrame".to_string());
+        without_gil(py, input.threads, || Ok(Report::new(input.select(dataset)?, &name, input.threads)))?
+    };
+    Ok(report)
+}
+
+/// The JSON profile document of a data file, as a dict
+#[pyfunction]
+#[pyo3(signature = (path, **options))]
+fn profile(py: Python<'_>, path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+    let input = InputOptions::from_kwargs(options)?;
+    let value = without_gil(py, input.threads, || {
+        let dataset = input.load(path)?;
+        profile_value(&dataset, path)
+    })?;
+    json_to_py(py, &value)
+}
+
+/// The profile dicts of several data files, in the order given. The files are
+/// read and analysed in parallel on `threads` threads (default: one per CPU).
+#[pyfunction]
+#[pyo3(signature = (paths, threads = None, **options))]
+fn profile_many(
+    py: Python<'_>,
+    paths: Vec<String>,
+    threads: Option<usize>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Vec<PyObject>> {
+    let input = InputOptions::from_kwargs(options)?;
+    let values = without_gil(py, threads, || {
+        paths
+            .par_iter()
+            .map(|path| profile_value(&input.load(path)?, path))
+            .collect::<Result<Vec<_>, Failure>>()
+    })?;
+    values.iter().map(|value| json_to_py(py, value)).collect()
+}
+
+/// The profile document of a dataset as a dict; `source` names it in the document
+fn profile_dict(
+    py: Python<'_>,
+    dataset: &Dataset,
+    source: &str,
+    threads: Option<usize>,
+) -> PyResult<PyObject> {
+    let value = without_gil(py, threads, || profile_value(dataset, source))?;
+    json_to_py(py, &value)
+}
+
+/// The profile document of a dataset as JSON, built without the GIL
+fn profile_value(dataset: &Dataset, source: &str) -> Result<serde_json::Value, Failure> {
+    let profile = analyze::profile(dataset).with_source(source);
+    serde_json::to_value(&profile).map_err(|e| Failure::Other(e.to_string()))
+}
+
+/// Profile a pandas or polars DataFrame, or any object exporting an Arrow
+/// stream. Returns the profile dict, or writes the report and returns its path
+/// when `output` is given. `threads` is the number of worker threads
+/// (default: one per CPU).
+#[pyfunction]
+#[pyo3(signature = (
+    df,
+    output = None,
+    format = "html",
+    name = "DataFrame",
+    include = None,
+    exclude = None,
+    sample = None,
+    threads = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn profile_dataframe(
+    py: Python<'_>,
+    df: &Bound<'_, PyAny>,
+    output: Option<&str>,
+    format: &str,
+    name: &str,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    sample: Option<usize>,
+    threads: Option<usize>,
+) -> PyResult<PyObject> {
+    let selection = InputOptions {
+        read: ReadOptions {
+            sample: sample.map(Sampling::head),
+            ..ReadOptions::default()
+        },
+        include: include.unwrap_or_default(),
+        exclude: exclude.unwrap_or_default(),
+        threads,
+    };
+    let dataset = dataframe_dataset(df)?;
+    let dataset = py.allow_threads(|| selection.select(dataset).map_err(Failure::from))?;
+    match output {
+        Some(output) => {
+            without_gil(py, threads, || {
+                let options = HtmlOptions::default();
+                report::generate_report(&dataset, name, output, format, &options)?;
+                Ok(())
+            })?;
+            Ok(output.into_py(py))
+        }
+        None => profile_dict(py, &dataset, name, threads),
+    }
+}
+
+/// Read a DataFrame through the Arrow C stream interface (`__arrow_c_stream__`)
+/// without serialising the frame; numeric columns are copied as numbers and
+/// the other values formatted as text, as for Arrow files (see
+/// [`formats::arrow_dataset`]). Objects
+/// without the interface go through `to_arrow()` (older polars) or
+/// `pyarrow.Table.from_pandas` (pandas before 3.0). The batches are taken from
+/// the stream with the GIL held and turned into cells without it.
+fn dataframe_dataset(df: &Bound<'_, PyAny>) -> PyResult<Dataset> {
+    let py = df.py();
+    let source = if df.hasattr("__arrow_c_stream__")? {
+        df.clone()
+    } else if df.hasattr("to_arrow")? {
+        df.call_method0("to_arrow")?
+    } else {
+        let pyarrow = py.import_bound("pyarrow").map_err(|_| {
+            PyTypeError::new_err(
+                "expected a pandas or polars DataFrame, or an object with __arrow_c_stream__ \
+                 (pandas DataFrames need pyarrow)",
+            )
+        })?;
+        let kwargs = PyDict::new_bound(py);
+        kwargs.set_item("preserve_index", false)?;
+        pyarrow
+            .getattr("Table")?
+            .call_method("from_pandas", (df,), Some(&kwargs))?
+    };
+    let capsule = source
+        .call_method0("__arrow_c_stream__")?
+        .downcast_into::<PyCapsule>()?;
+    if capsule.name()?.and_then(|n| n.to_str().ok()) != Some("arrow_array_stream") {
+        return Err(PyTypeError::new_err(
+            "__arrow_c_stream__ did not return an arrow_array_stream capsule",
+        ));
+    }
+    let stream = capsule.pointer() as *mut arrow_array::ffi_stream::FFI_ArrowArrayStream;
+    // SAFETY: the capsule holds a valid ArrowArrayStream; `from_raw` moves it
+    // out and leaves a released stream behind for the capsule destructor
+    let reader = unsafe { arrow_array::ffi_stream::ArrowArrayStreamReader::from_raw(stream) }
+        .map_err(|e| ParseError::new_err(e.to_string()))?;
+    let schema = arrow_array::RecordBatchReader::schema(&reader);
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ParseError::new_err(e.to_string()))?;
+    let dataset = py.allow_threads(|| {
+        let batches = arrow_array::RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        formats::arrow_dataset(batches).map_err(Failure::from)
+    })?;
+    Ok(dataset)
+}
+
+/// Write the HTML, Markdown (`md`) or JSON report of a data file and return its path
+#[pyfunction]
+#[pyo3(name = "report", signature = (path, output = "rapport.html", format = "html", **options))]
+fn write_report(
+    py: Python<'_>,
+    path: &str,
+    output: &str,
+    format: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let input = InputOptions::from_kwargs(options)?;
+    without_gil(py, input.threads, || {
+        let dataset = input.load(path)?;
+        report::generate_report(&dataset, path, output, format, &HtmlOptions::default())?;
+        Ok(())
+    })?;
+    Ok(output.to_string())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    delimiter = None,
+    quote = None,
+    escape = None,
+    has_header = None,
+    skip_rows = 0,
+    comment = None,
+    flexible = false,
+    encoding = None,
+    sheet = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn analyze_csv_py(
+    py: Python<'_>,
+    path: &str,
+    delimiter: Option<char>,
+    quote: Option<char>,
+    escape: Option<char>,
+    has_header: Option<bool>,
+    skip_rows: usize,
+    comment: Option<char>,
+    flexible: bool,
+    encoding: Option<&str>,
+    sheet: Option<String>,
+) -> PyResult<Report> {
+    let csv = CsvOptions {
+        delimiter: ascii_option("delimiter", delimiter)?,
+        quote: ascii_option("quote", quote)?,
+        escape: ascii_option("escape", escape)?,
+        has_headers: has_header,
+        skip_rows,
+        comment: ascii_option("comment", comment)?,
+        flexible,
+        encoding: encoding
+            .map(formats::parse_encoding)
+            .transpose()
+            .map_err(PyValueError::new_err)?,
+    };
+    let options = ReadOptions {
+        format: None,
+        csv,
+        sheet,
+        sample: None,
+    };
+    let report = py.allow_threads(|| {
+        let report = Report::new(formats::read_dataset_with_options(path, &options)?, path, None);
+        let (num, cat) = (&report.num_columns, &report.cat_columns);
+        let options = HtmlOptions::default();
+        report::render_report(&report.profile, num, cat, "rapport.html", "html", &options)?;
+        Ok::<_, Failure>(report)
+    })?;
+    Ok(report)
+}
+
+/// `analyze` as an awaitable: it runs on the event loop's default executor
+#[pyfunction]
+#[pyo3(signature = (source, name = None, **options))]
+fn analyze_async<'py>(
+    source: &Bound<'py, PyAny>,
+    name: Option<String>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = source.py();
+    let args = PyTuple::new_bound(py, [source.clone(), name.into_py(py).into_bound(py)]);
+    run_in_executor(wrap_pyfunction_bound!(analyze_source, py)?, args, options)
+}
+
+/// `profile` as an awaitable: it runs on the event loop's default executor
+#[pyfunction]
+#[pyo3(signature = (path, **options))]
+fn profile_async<'py>(
+    py: Python<'py>,
+    path: &str,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let args = PyTuple::new_bound(py, [path]);
+    run_in_executor(wrap_pyfunction_bound!(profile, py)?, args, options)
+}
+
+/// `report` as an awaitable: it runs on the event loop's default executor
+#[pyfunction]
+#[pyo3(signature = (path, output = "rapport.html", format = "html", **options))]
+fn report_async<'py>(
+    py: Python<'py>,
+    path: &str,
+    output: &str,
+    format: &str,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let args = PyTuple::new_bound(py, [path, output, format]);
+    run_in_executor(wrap_pyfunction_bound!(write_report, py)?, args, options)
+}
+
+/// Schedule `function(*args, **kwargs)` on the running event loop's default
+/// executor and return the future. The functions release the GIL while they
+/// work, so several of them run in parallel.
+fn run_in_executor<'py>(
+    function: Bound<'py, PyCFunction>,
+    args: Bound<'py, PyTuple>,
+    kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = function.py();
+    let mut partial_args = vec![function.into_any()];
+    partial_args.extend(args.iter());
+    let call = py
+        .import_bound("functools")?
+        .getattr("partial")?
+        .call(PyTuple::new_bound(py, partial_args), kwargs)?;
+    py.import_bound("asyncio")?
+        .call_method0("get_running_loop")?
+        .call_method1("run_in_executor", (py.None(), call))
+}
+
+/// An analyzer whose analyses are Python callables. They run on the profiling
+/// worker threads, each taking the GIL while it calls back.
+struct PyAnalyzer {
+    name: String,
+    column: Option<PyObject>,
+    dataset: Option<PyObject>,
+}
+
+/// What an analyzer callback returned: report sections, and the cleaning
+/// suggestions of a column
+#[derive(Default)]
+struct Findings {
+    sections: Vec<ReportSection>,
+    suggestions: Vec<String>,
+}
+
+impl PyAnalyzer {
+    /// Call `callback` and read its findings. An exception is printed with its
+    /// traceback and yields no findings, so one failing analyzer does not
+    /// lose the whole profile.
+    fn call(&self, py: Python<'_>, callback: &PyObject, arg: PyObject) -> Findings {
+        let findings = callback
+            .call1(py, (arg,))
+            .and_then(|result| self.findings(result.bind(py)));
+        findings.unwrap_or_else(|err| {
+            err.print(py);
+            Findings::default()
+        })
+    }
+
+    /// `None`, a dict with `title`, `items` and `suggestions` (all optional),
+    /// or a list of such dicts
+    fn findings(&self, result: &Bound<'_, PyAny>) -> PyResult<Findings> {
+        let mut findings = Findings::default();
+        if result.is_none() {
+            return Ok(findings);
+        }
+        let entries: Vec<Bound<'_, PyDict>> = match result.downcast::<PyDict>() {
+            Ok(entry) => vec![entry.clone()],
+            Err(_) => result.extract().map_err(|_| {
+                PyTypeError::new_err(format!(
+                    "analyzer '{}' must return None, a dict or a list of dicts",
+                    self.name
+                ))
+            })?,
+        };
+        for entry in entries {
+            let strings = |key: &str| -> PyResult<Vec<String>> {
+                entry.get_item(key)?.map_or(Ok(Vec::new()), |v| v.extract())
+            };
+            findings.suggestions.extend(strings("suggestions")?);
+            if let Some(title) = entry.get_item("title")? {
+                findings.sections.push(ReportSection {
+                    analyzer: self.name.clone(),
+                    title: title.extract()?,
+                    items: strings("items")?,
+                });
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// A column as the dict analyzer callbacks receive
+fn column_dict(py: Python<'_>, column: &TypedColumn) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("name", column.name)?;
+    dict.set_item("index", column.index)?;
+    dict.set_item("type", column.col_type)?;
+    match column.values {
+        ColumnValues::Numeric(values) => dict.set_item("values", values)?,
+        ColumnValues::Text(values) => dict.set_item("values", values)?,
+    }
+    Ok(dict.into_any().unbind())
+}
+
+impl Analyzer for PyAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn analyze_column(&self, column: &TypedColumn, profile: &mut ColumnProfile) {
+        let Some(callback) = &self.column else {
+            return;
+        };
+        let findings = Python::with_gil(|py| match column_dict(py, column) {
+            Ok(arg) => self.call(py, callback, arg),
+            Err(err) => {
+                err.print(py);
+                Findings::default()
+            }
+        });
+        profile.suggestions.extend(findings.suggestions);
+        profile.sections.extend(findings.sections);
+    }
+
+    fn analyze_dataset(&self, dataset: &DatasetColumns, profile: &mut Profile) {
+        let Some(callback) = &self.dataset else {
+            return;
+        };
+        let findings = Python::with_gil(|py| {
+            let columns: PyResult<Vec<PyObject>> =
+                dataset.columns().iter().map(|c| column_dict(py, c)).collect();
+            match columns {
+                Ok(columns) => self.call(py, callback, PyList::new_bound(py, columns).into_any().unbind()),
+                Err(err) => {
+                    err.print(py);
+                    Findings::default()
+                }
+            }
+        });
+        profile.sections.extend(findings.sections);
+    }
+}
+
+/// Run Python callables on every dataset profiled from now on. `column` is
+/// called with each column as a dict (`name`, `index`, `type`, `values`),
+/// `dataset` with the list of them; both return `None`, a dict with a `title`
+/// and `items` shown as a report section (plus `suggestions` for a column), or
+/// a list of such dicts. An analyzer of the same name is replaced, built-in
+/// ones included.
+#[pyfunction]
+#[pyo3(signature = (name, column = None, dataset = None))]
+fn register_analyzer(name: String, column: Option<PyObject>, dataset: Option<PyObject>) -> PyResult<()> {
+    if column.is_none() && dataset.is_none() {
+        return Err(PyValueError::new_err("expected a column or a dataset callback"));
+    }
+    analyzer::register(PyAnalyzer { name, column, dataset });
+    Ok(())
+}
+
+/// Stop running the analyzer called `name`; False when there is none.
+/// Raises ValueError for the `statistics` analyzer, which reports rely on.
+#[pyfunction]
+fn unregister_analyzer(name: &str) -> PyResult<bool> {
+    analyzer::unregister(name).map_err(PyValueError::new_err)
+}
+
+/// Names of the registered analyzers, in the order they run
+#[pyfunction]
+fn analyzers() -> Vec<String> {
+    analyzer::analyzer_names()
+}
+
+/// Numbers for the statistics functions: at least one, none of them NaN
+fn numbers(values: Vec<f64>) -> PyResult<Vec<f64>> {
+    if values.is_empty() {
+        return Err(PyValueError::new_err("expected at least one value"));
+    }
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(PyValueError::new_err("values must not be NaN"));
+    }
+    Ok(values)
+}
+
+#[pyfunction]
+fn mean(values: Vec<f64>) -> PyResult<f64> {
+    Ok(analyze::mean(&numbers(values)?))
+}
+
+#[pyfunction]
+fn median(values: Vec<f64>) -> PyResult<f64> {
+    Ok(analyze::median(&numbers(values)?))
+}
+
+/// Most frequent value of a sequence of strings
+#[pyfunction]
+fn mode(values: Vec<String>) -> PyResult<String> {
+    if values.is_empty() {
+        return Err(PyValueError::new_err("expected at least one value"));
+    }
+    Ok(analyze::mode(&values))
+}
+
+/// First and third quartiles, as a `(q1, q3)` tuple
+#[pyfunction]
+fn quartiles(values: Vec<f64>) -> PyResult<(f64, f64)> {
+    Ok(analyze::quartiles(&numbers(values)?))
+}
+
+/// Pearson correlation of two sequences of the same length; `None` when
+/// either of them is constant
+#[pyfunction]
+fn pearson_correlation(x: Vec<f64>, y: Vec<f64>) -> PyResult<Option<f64>> {
+    if x.len() != y.len() {
+        return Err(PyValueError::new_err(format!(
+            "sequences differ in length ({} and {})",
+            x.len(),
+            y.len()
+        )));
+    }
+    Ok(analyze::pearson_correlation(&numbers(x)?, &numbers(y)?))
+}
+
+/// Column type of a sequence of cells: numeric, boolean, date, categorical or empty
+#[pyfunction]
+fn infer_column_type(values: Vec<String>) -> &'static str {
+    analyze::infer_column_type(&values)
+}
+
+/// Cleaning suggestions for a sequence of cells
+#[pyfunction]
+fn cleaning_suggestions(values: Vec<String>) -> Vec<String> {
+    analyze::column_cleaning_suggestions(&values)
+}
+
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| json_to_py(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, items).into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
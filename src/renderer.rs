@@ -0,0 +1,233 @@
+// Pluggable report renderers: every output format of `datastory report` is a
+// `ReportRenderer` picked by name from a registry. Other crates can register
+// their own (a Confluence storage-format or LaTeX renderer, say) next to the
+// built-in HTML, interactive HTML, Markdown, JSON and PDF ones.
+use crate::analyze::Profile;
+use crate::report::{self, HtmlOptions};
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// What a renderer draws a report from: the profile holds every statistic,
+/// the split column values are there for charts
+pub struct ReportInput<'a> {
+    pub profile: &'a Profile,
+    /// Numeric values of every column (see `Dataset::split_columns`)
+    pub num_columns: &'a [Vec<f64>],
+    /// Non-numeric values of every column
+    pub cat_columns: &'a [Vec<String>],
+    pub options: &'a HtmlOptions,
+}
+
+/// An output format for dataset reports
+pub trait ReportRenderer: Send + Sync {
+    /// Name the renderer is picked by, e.g. `html` for `--format html`
+    fn name(&self) -> &str;
+
+    /// Other names accepted for it
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Extension of the report file when no file name is given
+    fn extension(&self) -> &str {
+        self.name()
+    }
+
+    /// What the output is called in status messages, e.g. "HTML report"
+    fn description(&self) -> &str;
+
+    /// Whether a report on data read from standard input goes to standard
+    /// output unless a file is named (text formats meant for piping)
+    fn stdout_by_default(&self) -> bool {
+        false
+    }
+
+    /// Write the report to `report_name`, or to standard output when it is
+    /// [`report::STDOUT`]; chart files go next to the report
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Renderers by name
+#[derive(Clone)]
+pub struct RendererRegistry {
+    renderers: Vec<Arc<dyn ReportRenderer>>,
+}
+
+impl RendererRegistry {
+    /// A registry without any renderer
+    pub fn new() -> Self {
+        RendererRegistry { renderers: Vec::new() }
+    }
+
+    /// Add a renderer, replacing the one registered under the same name
+    pub fn register(&mut self, renderer: impl ReportRenderer + 'static) {
+        self.renderers.retain(|r| r.name() != renderer.name());
+        self.renderers.push(Arc::new(renderer));
+    }
+
+    /// The renderer called `name`, or having it as an alias
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ReportRenderer>> {
+        let found = self.renderers.iter().find(|r| r.name() == name);
+        found
+            .or_else(|| self.renderers.iter().find(|r| r.aliases().contains(&name)))
+            .cloned()
+    }
+
+    /// Names of the registered renderers, in registration order
+    pub fn names(&self) -> Vec<String> {
+        self.renderers.iter().map(|r| r.name().to_string()).collect()
+    }
+}
+
+impl Default for RendererRegistry {
+    /// The built-in renderers
+    fn default() -> Self {
+        let mut registry = RendererRegistry::new();
+        registry.register(HtmlRenderer);
+        registry.register(InteractiveHtmlRenderer);
+        registry.register(MarkdownRenderer);
+        registry.register(JsonRenderer);
+        registry.register(PdfRenderer);
+        registry
+    }
+}
+
+/// Renderers `generate_report` and the command line pick from
+static REGISTRY: LazyLock<RwLock<RendererRegistry>> =
+    LazyLock::new(|| RwLock::new(RendererRegistry::default()));
+
+/// Make a renderer available to [`report::generate_report`] under its name,
+/// replacing any renderer of the same name (built-in ones included)
+pub fn register(renderer: impl ReportRenderer + 'static) {
+    REGISTRY.write().unwrap_or_else(PoisonError::into_inner).register(renderer);
+}
+
+/// The registered renderer called `name`
+pub fn renderer(name: &str) -> Option<Arc<dyn ReportRenderer>> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner).get(name)
+}
+
+/// Names of the registered renderers
+pub fn renderer_names() -> Vec<String> {
+    REGISTRY.read().unwrap_or_else(PoisonError::into_inner).names()
+}
+
+/// Static HTML report rendered from a template, charts as PNG files or inline SVG
+pub struct HtmlRenderer;
+
+impl ReportRenderer for HtmlRenderer {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn description(&self) -> &str {
+        "HTML report"
+    }
+
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let html = report::render_html_report(
+            input.profile,
+            input.num_columns,
+            input.cat_columns,
+            report_name,
+            input.options,
+        )?;
+        report::write_report(report_name, &html)
+    }
+}
+
+/// Single-file HTML report with sortable tables and zoomable SVG charts
+pub struct InteractiveHtmlRenderer;
+
+impl ReportRenderer for InteractiveHtmlRenderer {
+    fn name(&self) -> &str {
+        "interactive"
+    }
+
+    fn extension(&self) -> &str {
+        "html"
+    }
+
+    fn description(&self) -> &str {
+        "HTML report"
+    }
+
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        crate::interactive::generate_interactive_html_report(
+            input.profile,
+            input.num_columns,
+            input.cat_columns,
+            report_name,
+            input.options,
+        )
+    }
+}
+
+/// Markdown report rendered from a template, charts as PNG files
+pub struct MarkdownRenderer;
+
+impl ReportRenderer for MarkdownRenderer {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["markdown"]
+    }
+
+    fn description(&self) -> &str {
+        "Markdown report"
+    }
+
+    fn stdout_by_default(&self) -> bool {
+        true
+    }
+
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let markdown = report::render_markdown_report(
+            input.profile,
+            input.num_columns,
+            input.cat_columns,
+            report_name,
+            input.options,
+        )?;
+        report::write_report(report_name, &markdown)
+    }
+}
+
+/// The JSON profile document
+pub struct JsonRenderer;
+
+impl ReportRenderer for JsonRenderer {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "JSON profile"
+    }
+
+    fn stdout_by_default(&self) -> bool {
+        true
+    }
+
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        crate::profile::write_json_profile(input.profile, report_name)
+    }
+}
+
+/// PDF report; needs the LiberationSans fonts in the working directory
+pub struct PdfRenderer;
+
+impl ReportRenderer for PdfRenderer {
+    fn name(&self) -> &str {
+        "pdf"
+    }
+
+    fn description(&self) -> &str {
+        "PDF report"
+    }
+
+    fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        report::write_pdf_report(input.profile, report_name)
+    }
+}
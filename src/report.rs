@@ -1,6 +1,16 @@
-use crate::analyze::{mean, median, quartiles, mode, pearson_correlation};
-// use std::fs::File;
+use crate::analyze::{ColumnProfile, Profile, profile_columns};
+use crate::formats::{Dataset, Sheet};
+use crate::profile::build_profile;
+use crate::renderer::ReportInput;
+use crate::templates::{
+    ChartContext, ColumnContext, TemplateKind, build_context, load_template, render, sheet_context,
+};
+use std::fs::File;
 use std::io::Write;
+use std::path::Path;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use rayon::prelude::*;
 use genpdf::{
     Document,
     elements::{Break, Paragraph},
@@ -8,184 +18,588 @@ use genpdf::{
 
 pub fn generate_pdf_report(
     num_columns: &[Vec<f64>],
-    _cat_columns: &[Vec<String>],
+    cat_columns: &[Vec<String>],
     headers: &[String],
     path: &str,
     report_name: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    write_pdf_report(&build_profile(num_columns, cat_columns, headers, path), report_name)
+}
+
+/// Write the PDF report of a profile; it needs `LiberationSans-*.ttf` in the
+/// working directory
+pub fn write_pdf_report(profile: &Profile, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut doc = Document::new(genpdf::fonts::from_files(".", "LiberationSans", None)?);
     doc.set_title("Data Storytelling Report");
     doc.push(Paragraph::new(format!(
         "Narrative report for file: {}",
-        path
+        profile.source
     )));
+    if let Some(sample) = &profile.sample {
+        doc.push(Paragraph::new(format!(
+            "Statistics are computed from a sample. {}.",
+            sample.description
+        )));
+    }
     doc.push(Break::new(1));
     doc.push(Paragraph::new("Correlation analysis:"));
-    for i in 0..num_columns.len() {
-        for j in (i + 1)..num_columns.len() {
-            if !num_columns[i].is_empty()
-                && !num_columns[j].is_empty()
-                && let Some(corr) = crate::analyze::pearson_correlation(&num_columns[i], &num_columns[j])
-            {
-                doc.push(Paragraph::new(format!(
-                    "Pearson correlation between '{}' and '{}': {:.3}",
-                    headers[i], headers[j], corr
-                )));
-            }
-        }
+    for c in &profile.correlations {
+        doc.push(Paragraph::new(format!(
+            "Pearson correlation between '{}' and '{}': {:.3}",
+            c.a, c.b, c.r
+        )));
     }
-    for (i, col) in num_columns.iter().enumerate() {
-        if col.is_empty() {
-            continue;
-        }
-        let mean = mean(col);
-        let median = median(col);
-        let min = col.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max = col.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-        let (q1, q3) = quartiles(col);
-        let iqr = q3 - q1;
-        let lower = q1 - 1.5 * iqr;
-        let upper = q3 + 1.5 * iqr;
-        let outliers: Vec<_> = col
-            .iter()
-            .cloned()
-            .filter(|v| *v < lower || *v > upper)
-            .collect();
+    for section in &profile.sections {
         doc.push(Break::new(1));
-        doc.push(Paragraph::new(format!("Column '{}':", headers[i])));
-        doc.push(Paragraph::new("Type: numeric".to_string()));
-        doc.push(Paragraph::new(format!("Min: {:.2}, Max: {:.2}, Mean: {:.2}, Median: {:.2}, Q1: {:.2}, Q3: {:.2}, IQR: {:.2}, Outliers detected: {}", min, max, mean, median, q1, q3, iqr, outliers.len())));
-        if !outliers.is_empty() {
-            doc.push(Paragraph::new(format!("Extreme values: {:?}", outliers)));
+        doc.push(Paragraph::new(format!("{}:", section.title)));
+        for item in &section.items {
+            doc.push(Paragraph::new(format!("- {}", item)));
         }
-        let suggestions = crate::analyze::cleaning_suggestions(col);
-        doc.push(Paragraph::new("Cleaning suggestions:"));
-        for s in suggestions {
-            doc.push(Paragraph::new(format!("- {}", s)));
+    }
+    for column in &profile.columns {
+        doc.push(Break::new(1));
+        for line in pdf_section(column) {
+            doc.push(Paragraph::new(line));
         }
     }
-    // Optionally: save the PDF to disk
-    doc.render_to_file(report_name)?;
+    doc.render(create_report_file(report_name)?)?;
     Ok(())
 }
 
-pub fn generate_html_report(
-    num_columns: &[Vec<f64>],
-    cat_columns: &[Vec<String>],
-    headers: &[String],
-    path: &str,
-    report_name: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs::File;
-    let mut html = String::new();
-    html.push_str(&format!("<html><head><title>Data Storytelling Report</title></head><body>\n<h1>Data Storytelling Report</h1><h2>File: {}</h2>", path));
-    html.push_str("<h2>Correlation analysis</h2>");
-    for i in 0..num_columns.len() {
-        for j in (i + 1)..num_columns.len() {
-            if !num_columns[i].is_empty()
-                && !num_columns[j].is_empty()
-                && let Some(corr) = pearson_correlation(&num_columns[i], &num_columns[j])
-            {
-                html.push_str(&format!(
-                    "<li>Pearson correlation between '{}' and '{}': {:.3}</li>",
-                    headers[i], headers[j], corr
-                ));
-            }
+/// The lines of a column's section in the PDF report
+fn pdf_section(column: &ColumnProfile) -> Vec<String> {
+    let mut lines = vec![
+        format!("Column '{}':", column.name),
+        format!("Type: {}", column.col_type),
+    ];
+    if let Some(n) = &column.numeric {
+        let margin = n.mean_margin.map(|m| format!(" ± {:.2}", m)).unwrap_or_default();
+        lines.push(format!("Min: {:.2}, Max: {:.2}, Mean: {:.2}{}, Median: {:.2}, Q1: {:.2}, Q3: {:.2}, IQR: {:.2}, Outliers detected: {}", n.min, n.max, n.mean, margin, n.median, n.q1, n.q3, n.iqr, n.outliers.len()));
+        if !n.outliers.is_empty() {
+            lines.push(format!("Extreme values: {:?}", n.outliers));
         }
     }
-    // Numeric columns
-    for (i, col) in num_columns.iter().enumerate() {
-        if col.is_empty() {
-            continue;
-        }
-        let mean = mean(col);
-        let median = median(col);
-        let min = col.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max = col.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-        let (q1, q3) = quartiles(col);
-        let iqr = q3 - q1;
-        let lower = q1 - 1.5 * iqr;
-        let upper = q3 + 1.5 * iqr;
-        let outliers: Vec<_> = col.iter().cloned().filter(|v| *v < lower || *v > upper).collect();
-        html.push_str(&format!(
-            "<h2>Column '{}'</h2><ul><li>Type: numeric</li>", &headers[i]
+    if let Some(c) = &column.categorical {
+        lines.push(format!(
+            "Unique values: {}, Mode: '{}' ({} occurrences)",
+            c.unique, c.mode, c.mode_count
         ));
-        html.push_str(&format!("<li>Min: {:.2}</li>", min));
-        html.push_str(&format!("<li>Max: {:.2}</li>", max));
-        html.push_str(&format!("<li>Mean: {:.2}</li>", mean));
-        html.push_str(&format!("<li>Median: {:.2}</li>", median));
-        html.push_str(&format!("<li>Q1: {:.2}, Q3: {:.2}, IQR: {:.2}</li>", q1, q3, iqr));
-        html.push_str(&format!("<li>Outliers detected: {}</li>", outliers.len()));
-        if !outliers.is_empty() {
-            html.push_str(&format!("<li>Extreme values: {:?}</li>", outliers));
+    }
+    if !column.suggestions.is_empty() {
+        lines.push("Cleaning suggestions:".to_string());
+        for s in &column.suggestions {
+            lines.push(format!("- {}", s));
         }
-        html.push_str("</ul>");
-        html.push_str(&format!("<p>Narrative: Column '{}' has a mean value of {:.2}, ranging from {:.2} to {:.2}. The median is {:.2}. {} </p>",
-            &headers[i], mean, min, max, median,
-            if outliers.is_empty() {
-                "No extreme values detected."
-            } else {
-                "Extreme values were detected, which may indicate anomalies or data entry errors."
-            }
-        ));
-        let img_name = format!("assets/hist_{}.png", i);
-        let boxplot_img = format!("assets/boxplot_{}.png", i);
-        if let Err(e) = save_histogram(&img_name, col, &headers[i]) {
-            eprintln!("Error generating chart: {}", e);
+    }
+    for section in &column.sections {
+        lines.push(format!("{}:", section.title));
+        for item in &section.items {
+            lines.push(format!("- {}", item));
         }
-        html.push_str(&format!("<img src='{}' alt='Histogram {}'/><br/>", img_name, &headers[i]));
-        if let Err(e) = save_boxplot(&boxplot_img, col, &headers[i]) {
-            eprintln!("Error generating boxplot: {}", e);
+    }
+    lines
+}
+
+/// Options controlling how the HTML report is rendered and where its charts go
+#[derive(Debug, Clone)]
+pub struct HtmlOptions {
+    /// Embed every chart (as inline SVG) and all CSS into the report file itself
+    pub self_contained: bool,
+    /// Directory for chart images, resolved relative to the report's directory
+    pub assets_dir: String,
+    /// Path to a custom report template; the built-in one is used when unset
+    pub template: Option<String>,
+    /// Render charts; when false the report only contains text and tables
+    pub charts: bool,
+    /// Image format and size of the charts
+    pub chart: ChartOptions,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        HtmlOptions {
+            self_contained: false,
+            assets_dir: "assets".to_string(),
+            template: None,
+            charts: true,
+            chart: ChartOptions::default(),
         }
-        html.push_str(&format!("<img src='{}' alt='Boxplot {}'/><br/>", boxplot_img, &headers[i]));
     }
-    // Categorical columns
-    for (i, col) in cat_columns.iter().enumerate() {
-        if col.is_empty() {
-            continue;
+}
+
+/// Image format of chart files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartFormat {
+    /// Bitmap drawn with `BitMapBackend`
+    #[default]
+    Png,
+    /// Vector image drawn with `SVGBackend`: sharp at any zoom, and usually
+    /// smaller for simple charts
+    Svg,
+}
+
+impl ChartFormat {
+    /// File extension of charts in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChartFormat::Png => "png",
+            ChartFormat::Svg => "svg",
         }
-        let col_type = crate::analyze::detect_column_type(col);
-        let unique: std::collections::HashSet<_> = col.iter().cloned().collect();
-        let mode_val = mode(col);
-        let freq = col.iter().filter(|v| **v == mode_val).count();
-        html.push_str(&format!(
-            "<h2>Column '{}'</h2><ul><li>Type: {}</li>", &headers[i], col_type
-        ));
-        html.push_str(&format!("<li>Unique values: {}</li>", unique.len()));
-        html.push_str(&format!("<li>Mode: '{}' ({} occurrences)</li>", mode_val, freq));
-        html.push_str("</ul>");
-        html.push_str(&format!("<p>Narrative: Column '{}' contains {} unique values. The most frequent value is '{}' ({} times).</p>", &headers[i], unique.len(), mode_val, freq));
-        let bar_img = format!("assets/bar_{}.png", i);
-        if let Err(e) = save_bar_chart(&bar_img, col, &headers[i]) {
-            eprintln!("Error generating bar chart: {}", e);
+    }
+}
+
+impl std::str::FromStr for ChartFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(ChartFormat::Png),
+            "svg" => Ok(ChartFormat::Svg),
+            other => Err(format!("unknown chart format '{}' (expected png or svg)", other)),
+        }
+    }
+}
+
+/// Resolution charts are laid out at; other DPI values scale PNG charts
+pub const BASE_DPI: u32 = 96;
+
+/// Format and size of chart images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartOptions {
+    pub format: ChartFormat,
+    /// Width in pixels at [`BASE_DPI`]
+    pub width: u32,
+    /// Height in pixels at [`BASE_DPI`]
+    pub height: u32,
+    /// Resolution of PNG charts: at 192 DPI a 640×480 chart is 1280×960
+    /// pixels with the same layout. SVG charts are always `width`×`height`.
+    pub dpi: u32,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            format: ChartFormat::Png,
+            width: 640,
+            height: 480,
+            dpi: BASE_DPI,
+        }
+    }
+}
+
+impl ChartOptions {
+    /// Check the size and resolution are usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!("chart size {}x{} is empty", self.width, self.height));
+        }
+        if self.dpi == 0 {
+            return Err("chart DPI must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// How much PNG charts are scaled from their layout size
+    fn scale(&self) -> f64 {
+        self.dpi as f64 / BASE_DPI as f64
+    }
+
+    /// Size of PNG charts, in pixels
+    pub fn pixel_size(&self) -> (u32, u32) {
+        (scaled(self.width, self.scale()), scaled(self.height, self.scale()))
+    }
+
+    /// File name of a chart: `stem` with the extension of the format
+    pub fn file_name(&self, stem: &str) -> String {
+        format!("{}.{}", stem, self.format.extension())
+    }
+}
+
+/// A length laid out at [`BASE_DPI`], in pixels at `scale` times that
+fn scaled(length: u32, scale: f64) -> u32 {
+    (length as f64 * scale).round() as u32
+}
+
+/// Draw a chart into `filename` as the PNG or SVG file `options` ask for;
+/// `draw_png` and `draw_svg` draw the same chart, given the scale of the layout
+fn save_chart(
+    filename: &str,
+    options: &ChartOptions,
+    draw_png: impl FnOnce(&DrawingArea<BitMapBackend, Shift>, f64) -> Result<(), Box<dyn std::error::Error>>,
+    draw_svg: impl FnOnce(&DrawingArea<SVGBackend, Shift>, f64) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    options.validate()?;
+    match options.format {
+        ChartFormat::Png => {
+            let root = BitMapBackend::new(filename, options.pixel_size()).into_drawing_area();
+            draw_png(&root, options.scale())?;
+            root.present()?;
+        }
+        ChartFormat::Svg => {
+            let root = SVGBackend::new(filename, (options.width, options.height)).into_drawing_area();
+            draw_svg(&root, 1.0)?;
+            root.present()?;
         }
-        html.push_str(&format!("<img src='{}' alt='Bar Chart {}'/><br/>", bar_img, &headers[i]));
     }
-    html.push_str("</body></html>");
-    let mut file = File::create(report_name)?;
+    Ok(())
+}
 
-    file.write_all(html.as_bytes())?;
+/// Draw a chart as an SVG document of the size `options` ask for
+fn chart_svg(
+    options: &ChartOptions,
+    draw: impl FnOnce(&DrawingArea<SVGBackend, Shift>, f64) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    options.validate()?;
+    let mut buf = String::new();
+    {
+        let root = SVGBackend::with_string(&mut buf, (options.width, options.height)).into_drawing_area();
+        draw(&root, 1.0)?;
+        root.present()?;
+    }
+    Ok(buf)
+}
 
+/// Report name standing for standard output
+pub const STDOUT: &str = "-";
+
+/// Create the report file, or write to standard output when the name is `-`
+pub fn create_report_file(report_name: &str) -> std::io::Result<Box<dyn Write>> {
+    if report_name == STDOUT {
+        Ok(Box::new(std::io::stdout().lock()))
+    } else {
+        Ok(Box::new(File::create(report_name)?))
+    }
+}
+
+/// Write a rendered report to its file, or to standard output for `-`
+pub fn write_report(report_name: &str, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = create_report_file(report_name)?;
+    file.write_all(contents.as_bytes())?;
     Ok(())
 }
 
+const REPORT_CSS: &str = "body{font-family:sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+h1{border-bottom:2px solid #444}h2{margin-top:1.5em}\
+img,svg{max-width:100%;height:auto;display:block;margin:0.5em 0}";
+
+pub fn generate_html_report(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    path: &str,
+    report_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    generate_html_report_with_options(
+        num_columns,
+        cat_columns,
+        headers,
+        path,
+        report_name,
+        &HtmlOptions::default(),
+    )
+}
+
+pub fn generate_html_report_with_options(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    path: &str,
+    report_name: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile = build_profile(num_columns, cat_columns, headers, path);
+    let html = render_html_report(&profile, num_columns, cat_columns, report_name, options)?;
+    write_report(report_name, &html)
+}
+
+/// Render the HTML report of a profile as a string; charts are drawn from the
+/// columns the profile was computed from, and their files (unless
+/// self-contained) are written next to `report_name`
+pub fn render_html_report(
+    profile: &Profile,
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    report_name: &str,
+    options: &HtmlOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let source = load_template(options.template.as_deref(), TemplateKind::Html)?;
+    let mut context = build_context(profile);
+    context.css = REPORT_CSS.to_string();
+    attach_charts(&mut context.columns, num_columns, cat_columns, report_name, options, "");
+    render(&source, TemplateKind::Html, &context)
+}
+
+/// Render the report as Markdown; charts are written in the chosen chart format into the
+/// assets directory
+pub fn generate_markdown_report(
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    headers: &[String],
+    path: &str,
+    report_name: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile = build_profile(num_columns, cat_columns, headers, path);
+    let markdown = render_markdown_report(&profile, num_columns, cat_columns, report_name, options)?;
+    write_report(report_name, &markdown)
+}
+
+/// Render the Markdown report of a profile as a string; chart PNGs are
+/// written next to `report_name`
+pub fn render_markdown_report(
+    profile: &Profile,
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    report_name: &str,
+    options: &HtmlOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let source = load_template(options.template.as_deref(), TemplateKind::Markdown)?;
+    let mut context = build_context(profile);
+    let file_options = HtmlOptions {
+        self_contained: false,
+        ..options.clone()
+    };
+    attach_charts(&mut context.columns, num_columns, cat_columns, report_name, &file_options, "");
+    render(&source, TemplateKind::Markdown, &context)
+}
+
+/// Analyse a dataset and write its report with the renderer registered as
+/// `format` (see [`crate::renderer`]): built in are `html`, `interactive`,
+/// `md`, `json` and `pdf`. The dataset is profiled once and the renderer
+/// reads its statistics from that profile; charts are drawn from the values.
+pub fn generate_report(
+    dataset: &Dataset,
+    path: &str,
+    report_name: &str,
+    format: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Fail on an unknown format before the analysis
+    find_renderer(format)?;
+    let (num_columns, cat_columns) = dataset.split_columns();
+    let profile = profile_columns(
+        &num_columns,
+        &cat_columns,
+        &dataset.headers,
+        dataset.types.as_deref(),
+        dataset.sample.as_ref(),
+    )
+    .with_source(path);
+    render_report(&profile, &num_columns, &cat_columns, report_name, format, options)
+}
+
+/// Write the report of an existing profile with the renderer registered as
+/// `format`; `num_columns` and `cat_columns` are the values it was computed
+/// from, for the charts
+pub fn render_report(
+    profile: &Profile,
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    report_name: &str,
+    format: &str,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = ReportInput {
+        profile,
+        num_columns,
+        cat_columns,
+        options,
+    };
+    find_renderer(format)?.render(&input, report_name)
+}
+
+/// The renderer registered as `format`, or an unsupported format error
+/// listing the available ones
+fn find_renderer(
+    format: &str,
+) -> Result<std::sync::Arc<dyn crate::renderer::ReportRenderer>, crate::error::DataError> {
+    crate::renderer::renderer(format).ok_or_else(|| {
+        let message = format!(
+            "Unsupported report format '{}' (available: {})",
+            format,
+            crate::renderer::renderer_names().join(", ")
+        );
+        crate::error::DataError::UnsupportedFormat(message)
+    })
+}
+
+/// Render one HTML or Markdown report for a multi-sheet workbook, with the
+/// sections of each sheet under its own heading
+pub fn generate_sheets_report(
+    sheets: &[Sheet],
+    path: &str,
+    report_name: &str,
+    kind: TemplateKind,
+    options: &HtmlOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = load_template(options.template.as_deref(), kind)?;
+    let options = HtmlOptions {
+        self_contained: options.self_contained && kind == TemplateKind::Html,
+        ..options.clone()
+    };
+    let mut context = build_context(&build_profile(&[], &[], &[], path));
+    if kind == TemplateKind::Html {
+        context.css = REPORT_CSS.to_string();
+    }
+    for (k, sheet) in sheets.iter().enumerate() {
+        let (num_columns, cat_columns) = sheet.dataset.split_columns();
+        let headers = &sheet.dataset.headers;
+        let (types, sample) = (sheet.dataset.types.as_deref(), sheet.dataset.sample.as_ref());
+        let profile = profile_columns(&num_columns, &cat_columns, headers, types, sample);
+        let mut section = sheet_context(&sheet.name, &profile);
+        // Chart files of different sheets must not overwrite each other
+        let prefix = format!("sheet{}_", k + 1);
+        attach_charts(&mut section.columns, &num_columns, &cat_columns, report_name, &options, &prefix);
+        context.sheets.push(section);
+    }
+    let output = render(&source, kind, &context)?;
+    write_report(report_name, &output)
+}
+
+/// Render the histogram, boxplot or bar chart of every column section, one
+/// column per worker thread; chart file names start with `prefix`
+fn attach_charts(
+    columns: &mut [ColumnContext],
+    num_columns: &[Vec<f64>],
+    cat_columns: &[Vec<String>],
+    report_name: &str,
+    options: &HtmlOptions,
+    prefix: &str,
+) {
+    if !options.charts {
+        return;
+    }
+    let report_dir = Path::new(report_name)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    columns.par_iter_mut().for_each(|column| {
+        let i = column.profile.index;
+        let name = column.profile.name.as_str();
+        if column.profile.numeric.is_some() {
+            let col = &num_columns[i];
+            match embed_chart(
+                options,
+                report_dir,
+                "histogram",
+                &format!("{}hist_{}", prefix, i),
+                &format!("Histogram {}", name),
+                |f| save_histogram(f, col, name, &options.chart),
+                || svg_histogram(col, name, &options.chart),
+            ) {
+                Ok(chart) => column.charts.push(chart),
+                Err(e) => eprintln!("Error generating chart: {}", e),
+            }
+            match embed_chart(
+                options,
+                report_dir,
+                "boxplot",
+                &format!("{}boxplot_{}", prefix, i),
+                &format!("Boxplot {}", name),
+                |f| save_boxplot(f, col, name, &options.chart),
+                || svg_boxplot(col, name, &options.chart),
+            ) {
+                Ok(chart) => column.charts.push(chart),
+                Err(e) => eprintln!("Error generating boxplot: {}", e),
+            }
+        } else {
+            let col = &cat_columns[i];
+            match embed_chart(
+                options,
+                report_dir,
+                "bar",
+                &format!("{}bar_{}", prefix, i),
+                &format!("Bar Chart {}", name),
+                |f| save_bar_chart(f, col, name, &options.chart),
+                || svg_bar_chart(col, name, &options.chart),
+            ) {
+                Ok(chart) => column.charts.push(chart),
+                Err(e) => eprintln!("Error generating bar chart: {}", e),
+            }
+        }
+    });
+}
+
+/// Render a chart either as inline SVG (self-contained reports) or as a PNG
+/// or SVG file named `file_stem` in the assets directory next to the report.
+pub(crate) fn embed_chart(
+    options: &HtmlOptions,
+    report_dir: &Path,
+    kind: &str,
+    file_stem: &str,
+    alt: &str,
+    save: impl Fn(&str) -> Result<(), Box<dyn std::error::Error>>,
+    svg: impl Fn() -> Result<String, Box<dyn std::error::Error>>,
+) -> Result<ChartContext, Box<dyn std::error::Error>> {
+    if options.self_contained {
+        return Ok(ChartContext {
+            kind: kind.to_string(),
+            alt: alt.to_string(),
+            src: None,
+            svg: Some(svg()?),
+        });
+    }
+    let file_name = options.chart.file_name(file_stem);
+    let out_dir = report_dir.join(&options.assets_dir);
+    std::fs::create_dir_all(&out_dir)?;
+    let out_file = out_dir.join(&file_name);
+    save(&out_file.to_string_lossy())?;
+    // Links stay relative to the report so the report and its assets can be moved together
+    let src = Path::new(&options.assets_dir)
+        .join(&file_name)
+        .to_string_lossy()
+        .replace('\\', "/");
+    Ok(ChartContext {
+        kind: kind.to_string(),
+        alt: alt.to_string(),
+        src: Some(src),
+        svg: None,
+    })
+}
+
+/// Write a histogram as a PNG or SVG file
 pub fn save_histogram(
     filename: &str,
     data: &[f64],
     colname: &str,
+    options: &ChartOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use plotters::prelude::*;
-    let root = BitMapBackend::new(filename, (640, 480)).into_drawing_area();
+    save_chart(
+        filename,
+        options,
+        |root, scale| draw_histogram(root, data, colname, scale),
+        |root, scale| draw_histogram(root, data, colname, scale),
+    )
+}
+
+/// Render a histogram as an SVG document
+pub fn svg_histogram(
+    data: &[f64],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    chart_svg(options, |root, scale| draw_histogram(root, data, colname, scale))
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    data: &[f64],
+    colname: &str,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
     root.fill(&WHITE)?;
     let min = data.iter().cloned().fold(f64::INFINITY, f64::min);
     let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-    let mut chart = ChartBuilder::on(&root)
-        .caption(format!("Histogram - {}", colname), ("sans-serif", 30))
-        .margin(20)
-        .x_label_area_size(40)
-        .y_label_area_size(40)
+    let mut chart = ChartBuilder::on(root)
+        .caption(format!("Histogram - {}", colname), ("sans-serif", 30.0 * scale))
+        .margin(scaled(20, scale))
+        .x_label_area_size(scaled(40, scale))
+        .y_label_area_size(scaled(40, scale))
         .build_cartesian_2d(min..max, 0..data.len() as u32)?;
-    chart.configure_mesh().draw()?;
+    chart.configure_mesh().label_style(("sans-serif", 12.0 * scale)).draw()?;
     let bin_count = 20;
     let bin_width = (max - min) / bin_count as f64;
     let mut bins = vec![0u32; bin_count];
@@ -201,13 +615,40 @@ pub fn save_histogram(
     }))?;
     Ok(())
 }
+
+/// Write a boxplot as a PNG or SVG file
 pub fn save_boxplot(
     filename: &str,
     data: &[f64],
     colname: &str,
+    options: &ChartOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use plotters::prelude::*;
-    let root = BitMapBackend::new(filename, (640, 480)).into_drawing_area();
+    save_chart(
+        filename,
+        options,
+        |root, scale| draw_boxplot(root, data, colname, scale),
+        |root, scale| draw_boxplot(root, data, colname, scale),
+    )
+}
+
+/// Render a boxplot as an SVG document
+pub fn svg_boxplot(
+    data: &[f64],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    chart_svg(options, |root, scale| draw_boxplot(root, data, colname, scale))
+}
+
+fn draw_boxplot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    data: &[f64],
+    colname: &str,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
     root.fill(&WHITE)?;
     let min = data.iter().cloned().fold(f64::INFINITY, f64::min);
     let max = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
@@ -224,13 +665,17 @@ pub fn save_boxplot(
         .cloned()
         .filter(|v| *v <= (q3 + 1.5 * iqr))
         .fold(q3, f64::max);
-    let mut chart = ChartBuilder::on(&root)
-        .caption(format!("Boxplot - {}", colname), ("sans-serif", 30))
-        .margin(20)
-        .x_label_area_size(40)
-        .y_label_area_size(40)
+    let mut chart = ChartBuilder::on(root)
+        .caption(format!("Boxplot - {}", colname), ("sans-serif", 30.0 * scale))
+        .margin(scaled(20, scale))
+        .x_label_area_size(scaled(40, scale))
+        .y_label_area_size(scaled(40, scale))
         .build_cartesian_2d(0..1, min..max)?;
-    chart.configure_mesh().disable_x_mesh().draw()?;
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 12.0 * scale))
+        .disable_x_mesh()
+        .draw()?;
     chart.draw_series(std::iter::once(Rectangle::new(
         [(0, q1), (1, q3)],
         RED.filled(),
@@ -249,13 +694,40 @@ pub fn save_boxplot(
     )))?;
     Ok(())
 }
+
+/// Write a bar chart as a PNG or SVG file
 pub fn save_bar_chart(
     filename: &str,
     data: &[String],
     colname: &str,
+    options: &ChartOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use plotters::prelude::*;
-    let root = BitMapBackend::new(filename, (640, 480)).into_drawing_area();
+    save_chart(
+        filename,
+        options,
+        |root, scale| draw_bar_chart(root, data, colname, scale),
+        |root, scale| draw_bar_chart(root, data, colname, scale),
+    )
+}
+
+/// Render a bar chart as an SVG document
+pub fn svg_bar_chart(
+    data: &[String],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    chart_svg(options, |root, scale| draw_bar_chart(root, data, colname, scale))
+}
+
+fn draw_bar_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    data: &[String],
+    colname: &str,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
     root.fill(&WHITE)?;
     // Compute frequencies
     let mut freq_map = std::collections::HashMap::new();
@@ -263,13 +735,13 @@ pub fn save_bar_chart(
         *freq_map.entry(val).or_insert(0) += 1;
     }
     let max_count = *freq_map.values().max().unwrap_or(&0);
-    let mut chart = ChartBuilder::on(&root)
-        .caption(format!("Bar Chart - {}", colname), ("sans-serif", 30))
-        .margin(20)
-        .x_label_area_size(40)
-        .y_label_area_size(40)
+    let mut chart = ChartBuilder::on(root)
+        .caption(format!("Bar Chart - {}", colname), ("sans-serif", 30.0 * scale))
+        .margin(scaled(20, scale))
+        .x_label_area_size(scaled(40, scale))
+        .y_label_area_size(scaled(40, scale))
         .build_cartesian_2d(0..freq_map.len(), 0..max_count)?;
-    chart.configure_mesh().draw()?;
+    chart.configure_mesh().label_style(("sans-serif", 12.0 * scale)).draw()?;
     for (i, (_val, count)) in freq_map.iter().enumerate() {
         chart.draw_series(std::iter::once(Rectangle::new(
             [(i, 0), (i + 1, *count)], BLUE.filled(),
@@ -277,3 +749,210 @@ pub fn save_bar_chart(
     }
     Ok(())
 }
+
+/// The coloured square of a series in a chart legend
+fn legend_swatch(x: i32, y: i32, color: RGBAColor, scale: f64) -> Rectangle<(i32, i32)> {
+    let half = scaled(5, scale) as i32;
+    Rectangle::new([(x, y - half), (x + 2 * half, y + half)], color.filled())
+}
+
+/// Write an overlaid histogram of two columns as a PNG or SVG file
+pub fn save_overlay_histogram(
+    filename: &str,
+    reference: &[f64],
+    current: &[f64],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    save_chart(
+        filename,
+        options,
+        |root, scale| draw_overlay_histogram(root, reference, current, colname, scale),
+        |root, scale| draw_overlay_histogram(root, reference, current, colname, scale),
+    )
+}
+
+/// Render a reference-vs-current overlaid histogram as an SVG document
+pub fn svg_overlay_histogram(
+    reference: &[f64],
+    current: &[f64],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    chart_svg(options, |root, scale| draw_overlay_histogram(root, reference, current, colname, scale))
+}
+
+/// Two histograms on shared bins, as proportions so differently sized datasets compare
+fn draw_overlay_histogram<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    reference: &[f64],
+    current: &[f64],
+    colname: &str,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let all = reference.iter().chain(current.iter()).cloned();
+    let mut min = all.clone().fold(f64::INFINITY, f64::min);
+    let mut max = all.fold(f64::NEG_INFINITY, f64::max);
+    if max <= min {
+        min -= 0.5;
+        max += 0.5;
+    }
+    let bin_count = 20;
+    let bin_width = (max - min) / bin_count as f64;
+    let proportions = |data: &[f64]| {
+        let mut bins = vec![0.0f64; bin_count];
+        for &v in data {
+            let idx = (((v - min) / bin_width).floor() as usize).min(bin_count - 1);
+            bins[idx] += 1.0;
+        }
+        let total = data.len().max(1) as f64;
+        bins.iter().map(|c| c / total).collect::<Vec<_>>()
+    };
+    let ref_bins = proportions(reference);
+    let cur_bins = proportions(current);
+    let y_max = ref_bins.iter().chain(cur_bins.iter()).cloned().fold(0.0, f64::max) * 1.1;
+    let mut chart = ChartBuilder::on(root)
+        .caption(format!("Distribution drift - {}", colname), ("sans-serif", 30.0 * scale))
+        .margin(scaled(20, scale))
+        .x_label_area_size(scaled(40, scale))
+        .y_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(min..max, 0.0..y_max.max(0.01))?;
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 12.0 * scale))
+        .y_desc("Share of rows")
+        .draw()?;
+    for (bins, color, label) in [(&ref_bins, BLUE, "Reference"), (&cur_bins, RED, "Current")] {
+        chart
+            .draw_series(bins.iter().enumerate().map(|(i, &p)| {
+                let x0 = min + i as f64 * bin_width;
+                Rectangle::new([(x0, 0.0), (x0 + bin_width, p)], color.mix(0.4).filled())
+            }))?
+            .label(label)
+            .legend(move |(x, y)| legend_swatch(x, y, color.mix(0.4), scale));
+    }
+    chart
+        .configure_series_labels()
+        .label_font(("sans-serif", 12.0 * scale))
+        .legend_area_size(scaled(30, scale))
+        .margin(scaled(10, scale))
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Write a grouped bar chart of two columns as a PNG or SVG file
+pub fn save_overlay_bar_chart(
+    filename: &str,
+    reference: &[String],
+    current: &[String],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    save_chart(
+        filename,
+        options,
+        |root, scale| draw_overlay_bar_chart(root, reference, current, colname, scale),
+        |root, scale| draw_overlay_bar_chart(root, reference, current, colname, scale),
+    )
+}
+
+/// Render a reference-vs-current grouped bar chart as an SVG document
+pub fn svg_overlay_bar_chart(
+    reference: &[String],
+    current: &[String],
+    colname: &str,
+    options: &ChartOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    chart_svg(options, |root, scale| draw_overlay_bar_chart(root, reference, current, colname, scale))
+}
+
+/// Category shares side by side for the most frequent values of both datasets
+fn draw_overlay_bar_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    reference: &[String],
+    current: &[String],
+    colname: &str,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    const MAX_BARS: usize = 20;
+    root.fill(&WHITE)?;
+    let mut ref_freq = std::collections::HashMap::new();
+    for val in reference {
+        *ref_freq.entry(val).or_insert(0usize) += 1;
+    }
+    let mut cur_freq = std::collections::HashMap::new();
+    for val in current {
+        *cur_freq.entry(val).or_insert(0usize) += 1;
+    }
+    let mut categories: Vec<&String> = ref_freq.keys().chain(cur_freq.keys()).cloned().collect();
+    categories.sort();
+    categories.dedup();
+    let total = |c: &&String| ref_freq.get(c).unwrap_or(&0) + cur_freq.get(c).unwrap_or(&0);
+    categories.sort_by(|a, b| total(b).cmp(&total(a)).then_with(|| a.cmp(b)));
+    categories.truncate(MAX_BARS);
+    let ref_total = reference.len().max(1) as f64;
+    let cur_total = current.len().max(1) as f64;
+    let share = |freq: &std::collections::HashMap<&String, usize>, c: &String, total: f64| {
+        *freq.get(c).unwrap_or(&0) as f64 / total
+    };
+    let y_max = categories
+        .iter()
+        .map(|c| share(&ref_freq, c, ref_total).max(share(&cur_freq, c, cur_total)))
+        .fold(0.0, f64::max)
+        * 1.1;
+    let mut chart = ChartBuilder::on(root)
+        .caption(format!("Distribution drift - {}", colname), ("sans-serif", 30.0 * scale))
+        .margin(scaled(20, scale))
+        .x_label_area_size(scaled(40, scale))
+        .y_label_area_size(scaled(50, scale))
+        .build_cartesian_2d(-0.5..categories.len().max(1) as f64 - 0.5, 0.0..y_max.max(0.01))?;
+    // Each category is centred on its index so the axis labels can name it
+    let label = |x: &f64| {
+        let idx = x.round();
+        if (x - idx).abs() < 1e-9 && idx >= 0.0 {
+            categories.get(idx as usize).map(|c| c.to_string()).unwrap_or_default()
+        } else {
+            String::new()
+        }
+    };
+    chart
+        .configure_mesh()
+        .label_style(("sans-serif", 12.0 * scale))
+        .disable_x_mesh()
+        .x_labels(categories.len().max(1))
+        .x_label_formatter(&label)
+        .y_desc("Share of rows")
+        .draw()?;
+    chart
+        .draw_series(categories.iter().enumerate().map(|(i, c)| {
+            let x = i as f64;
+            Rectangle::new([(x - 0.45, 0.0), (x, share(&ref_freq, c, ref_total))], BLUE.mix(0.6).filled())
+        }))?
+        .label("Reference")
+        .legend(move |(x, y)| legend_swatch(x, y, BLUE.mix(0.6), scale));
+    chart
+        .draw_series(categories.iter().enumerate().map(|(i, c)| {
+            let x = i as f64;
+            Rectangle::new([(x, 0.0), (x + 0.45, share(&cur_freq, c, cur_total))], RED.mix(0.6).filled())
+        }))?
+        .label("Current")
+        .legend(move |(x, y)| legend_swatch(x, y, RED.mix(0.6), scale));
+    chart
+        .configure_series_labels()
+        .label_font(("sans-serif", 12.0 * scale))
+        .legend_area_size(scaled(30, scale))
+        .margin(scaled(10, scale))
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
@@ -0,0 +1,430 @@
+// Row sampling for profiling large datasets quickly: the first N rows, a
+// reservoir sample drawn while the file streams by, a sample stratified by a
+// categorical column, or a random fraction of the rows. Random draws use a
+// fixed seed so the same command gives the same sample.
+use crate::formats::Dataset;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Seed of the random draws when none is given
+pub const DEFAULT_SEED: u64 = 42;
+
+/// How sampled rows are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleMethod {
+    /// The first rows of the file
+    Head,
+    /// A uniform sample of a fixed size, drawn in one pass
+    Reservoir,
+    /// Every value of a column represented in proportion to its frequency
+    Stratified,
+    /// Each row kept with the same probability
+    Fraction,
+}
+
+impl SampleMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SampleMethod::Head => "head",
+            SampleMethod::Reservoir => "reservoir",
+            SampleMethod::Stratified => "stratified",
+            SampleMethod::Fraction => "fraction",
+        }
+    }
+}
+
+impl std::str::FromStr for SampleMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "head" => Ok(SampleMethod::Head),
+            "reservoir" => Ok(SampleMethod::Reservoir),
+            "stratified" => Ok(SampleMethod::Stratified),
+            "fraction" | "random" => Ok(SampleMethod::Fraction),
+            other => Err(format!(
+                "unknown sample method '{}' (expected head, reservoir, stratified or fraction)",
+                other
+            )),
+        }
+    }
+}
+
+/// How much of the data a sample keeps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleSize {
+    Rows(usize),
+    /// A share of the rows, in (0, 1]
+    Fraction(f64),
+}
+
+/// A sampling request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampling {
+    pub method: SampleMethod,
+    pub size: SampleSize,
+    /// Column whose values are the strata of a stratified sample
+    pub stratify_by: Option<String>,
+    pub seed: u64,
+}
+
+/// What the rows of a dataset were sampled from; stated in reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleInfo {
+    pub method: SampleMethod,
+    /// Rows in the sample
+    pub rows: usize,
+    /// Rows the sample was drawn from; unknown when reading stopped after
+    /// the first rows
+    pub population: Option<usize>,
+    /// Requested share of the rows, for fraction sizes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fraction: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stratify_by: Option<String>,
+    /// Seed of the random draw; none for `head`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl Sampling {
+    /// The first `n` rows, as `--sample N` always did
+    pub fn head(n: usize) -> Sampling {
+        Sampling {
+            method: SampleMethod::Head,
+            size: SampleSize::Rows(n),
+            stratify_by: None,
+            seed: DEFAULT_SEED,
+        }
+    }
+
+    /// Combine the sampling options; `None` when no sample size is given.
+    /// Without an explicit method, `stratify_by` means a stratified sample, a
+    /// fraction a random fraction and a row count the first rows.
+    pub fn new(
+        method: Option<SampleMethod>,
+        rows: Option<usize>,
+        fraction: Option<f64>,
+        stratify_by: Option<String>,
+        seed: Option<u64>,
+    ) -> Result<Option<Sampling>, String> {
+        let size = match (rows, fraction) {
+            (Some(_), Some(_)) => {
+                return Err("give either a sample size or a sample fraction, not both".to_string());
+            }
+            (Some(n), None) => SampleSize::Rows(n),
+            (None, Some(f)) if f > 0.0 && f <= 1.0 => SampleSize::Fraction(f),
+            (None, Some(f)) => {
+                return Err(format!("the sample fraction must be in (0, 1], got {}", f));
+            }
+            (None, None) if method.is_some() || stratify_by.is_some() || seed.is_some() => {
+                return Err("sampling needs a sample size or a sample fraction".to_string());
+            }
+            (None, None) => return Ok(None),
+        };
+        let method = method.unwrap_or(match (&stratify_by, size) {
+            (Some(_), _) => SampleMethod::Stratified,
+            (None, SampleSize::Fraction(_)) => SampleMethod::Fraction,
+            (None, SampleSize::Rows(_)) => SampleMethod::Head,
+        });
+        match (method, size, &stratify_by) {
+            (SampleMethod::Reservoir, SampleSize::Fraction(_), _) => {
+                Err("reservoir sampling needs a sample size, not a fraction".to_string())
+            }
+            (SampleMethod::Fraction, SampleSize::Rows(_), _) => {
+                Err("fraction sampling needs a sample fraction, not a size".to_string())
+            }
+            (SampleMethod::Stratified, _, None) => {
+                Err("stratified sampling needs a column to stratify by".to_string())
+            }
+            (m, _, Some(_)) if m != SampleMethod::Stratified => {
+                Err(format!("{} sampling does not stratify by a column", m.as_str()))
+            }
+            _ => Ok(Some(Sampling {
+                method,
+                size,
+                stratify_by,
+                seed: seed.unwrap_or(DEFAULT_SEED),
+            })),
+        }
+    }
+
+    /// Whether rows can be sampled one at a time as they are read, without
+    /// keeping the others. A head sample of a row count also lets the reader
+    /// stop once it is full; see [`RowSampler::is_full`]
+    pub fn streams(&self) -> bool {
+        match self.method {
+            SampleMethod::Reservoir | SampleMethod::Fraction => true,
+            SampleMethod::Head => matches!(self.size, SampleSize::Rows(_)),
+            SampleMethod::Stratified => false,
+        }
+    }
+
+    /// A sampler fed the rows of a file as they are read; see [`Sampling::streams`]
+    pub fn sampler<T>(&self) -> RowSampler<T> {
+        RowSampler {
+            sampling: self.clone(),
+            rng: StdRng::seed_from_u64(self.seed),
+            seen: 0,
+            kept: Vec::new(),
+        }
+    }
+
+    /// Sample the rows of a loaded dataset. The rows keep their order; a
+    /// sample holding every row is no sample and leaves `dataset.sample` unset.
+    pub fn apply(&self, dataset: Dataset) -> Result<Dataset, Box<dyn std::error::Error>> {
+        let strata = match self.method {
+            SampleMethod::Stratified => {
+                let name = self.stratify_by.as_deref().unwrap_or_default();
+                let column =
+                    dataset.column(name).ok_or_else(|| format!("Unknown column '{}'", name))?;
+                Some(column)
+            }
+            _ => None,
+        };
+        let Dataset { headers, rows, types, numbers, .. } = dataset;
+        let population = rows.len();
+        // The rows are all loaded, so a head sample knows their count
+        let (keep, rows, sample): (Vec<usize>, Vec<Vec<String>>, _) =
+            if matches!(self.method, SampleMethod::Reservoir | SampleMethod::Fraction) {
+                let mut sampler = self.sampler();
+                for row in rows.into_iter().enumerate() {
+                    sampler.push(row);
+                }
+                let (kept, sample) = sampler.finish();
+                let (keep, rows) = kept.into_iter().unzip();
+                (keep, rows, sample)
+            } else {
+                let keep: Vec<usize> = match &strata {
+                    Some(values) => self.stratified_rows(values),
+                    None => (0..self.quota(population)).collect(),
+                };
+                let mut wanted = keep.iter().copied().peekable();
+                let rows: Vec<Vec<String>> = rows
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| wanted.next_if_eq(i).is_some())
+                    .map(|(_, row)| row)
+                    .collect();
+                let sample = self.info(rows.len(), Some(population));
+                (keep, rows, sample)
+            };
+        let numbers = numbers.map(|columns| {
+            columns
+                .iter()
+                .map(|numbers| numbers.as_ref().map(|n| n.select(&keep)))
+                .collect()
+        });
+        Ok(Dataset { headers, rows, sample, types, numbers })
+    }
+
+    /// Rows to keep out of `population`
+    fn quota(&self, population: usize) -> usize {
+        match self.size {
+            SampleSize::Rows(n) => n.min(population),
+            SampleSize::Fraction(f) => ((population as f64 * f).round() as usize).min(population),
+        }
+    }
+
+    /// Indices (ascending) of a sample drawn separately from each of the
+    /// `values` of the stratifying column: proportional allocation with
+    /// largest remainders for a row count, the same fraction of every stratum
+    /// for a fraction
+    fn stratified_rows(&self, values: &[String]) -> Vec<usize> {
+        let mut strata: Vec<Vec<usize>> = Vec::new();
+        let mut index = std::collections::HashMap::new();
+        for (i, value) in values.iter().enumerate() {
+            let k = *index.entry(value.as_str()).or_insert_with(|| {
+                strata.push(Vec::new());
+                strata.len() - 1
+            });
+            strata[k].push(i);
+        }
+        let total = values.len().max(1) as f64;
+        let target = self.quota(values.len());
+        let shares: Vec<f64> = strata
+            .iter()
+            .map(|s| match self.size {
+                SampleSize::Rows(_) => target as f64 * s.len() as f64 / total,
+                SampleSize::Fraction(f) => (s.len() as f64 * f).round(),
+            })
+            .collect();
+        let mut quotas: Vec<usize> = shares.iter().map(|s| s.floor() as usize).collect();
+        if let SampleSize::Rows(_) = self.size {
+            let mut order: Vec<usize> = (0..strata.len()).collect();
+            order.sort_by(|&a, &b| {
+                let rem = |k: usize| shares[k] - shares[k].floor();
+                rem(b).total_cmp(&rem(a)).then(a.cmp(&b))
+            });
+            let missing = target - quotas.iter().sum::<usize>();
+            for &k in order.iter().take(missing) {
+                quotas[k] += 1;
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut keep: Vec<usize> = strata
+            .iter()
+            .zip(quotas)
+            .flat_map(|(stratum, quota)| {
+                rand::seq::index::sample(&mut rng, stratum.len(), quota.min(stratum.len()))
+                    .into_iter()
+                    .map(|j| stratum[j])
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        keep.sort_unstable();
+        keep
+    }
+
+    fn info(&self, rows: usize, population: Option<usize>) -> Option<SampleInfo> {
+        if population == Some(rows) {
+            return None;
+        }
+        Some(SampleInfo {
+            method: self.method,
+            rows,
+            population,
+            fraction: match self.size {
+                SampleSize::Fraction(f) => Some(f),
+                SampleSize::Rows(_) => None,
+            },
+            stratify_by: self.stratify_by.clone(),
+            seed: (self.method != SampleMethod::Head).then_some(self.seed),
+        })
+    }
+}
+
+/// Samples rows one at a time while a file is read, keeping at most the
+/// sample in memory
+pub struct RowSampler<T> {
+    sampling: Sampling,
+    rng: StdRng,
+    seen: usize,
+    /// Kept items with their position in the input
+    kept: Vec<(usize, T)>,
+}
+
+impl<T> RowSampler<T> {
+    /// Whether a head sample is full and a row past it was seen, so the rest
+    /// of the input can be left unread
+    pub fn is_full(&self) -> bool {
+        match (self.sampling.method, self.sampling.size) {
+            (SampleMethod::Head, SampleSize::Rows(n)) => self.seen > n,
+            _ => false,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        let position = self.seen;
+        self.seen += 1;
+        match (self.sampling.method, self.sampling.size) {
+            (SampleMethod::Reservoir, SampleSize::Rows(n)) => {
+                if self.kept.len() < n {
+                    self.kept.push((position, item));
+                } else {
+                    // Algorithm R: the new item replaces a random kept one with probability n / seen
+                    let j = self.rng.gen_range(0..self.seen);
+                    if j < n {
+                        self.kept[j] = (position, item);
+                    }
+                }
+            }
+            (SampleMethod::Fraction, SampleSize::Fraction(f)) => {
+                if self.rng.r#gen::<f64>() < f {
+                    self.kept.push((position, item));
+                }
+            }
+            (_, SampleSize::Rows(n)) => {
+                if self.kept.len() < n {
+                    self.kept.push((position, item));
+                }
+            }
+            (_, SampleSize::Fraction(_)) => self.kept.push((position, item)),
+        }
+    }
+
+    /// The sampled items in input order, and what they were sampled from. The
+    /// population is unknown when the input was left unread once full
+    pub fn finish(mut self) -> (Vec<T>, Option<SampleInfo>) {
+        let population = (!self.is_full()).then_some(self.seen);
+        self.kept.sort_unstable_by_key(|(position, _)| *position);
+        let items: Vec<T> = self.kept.into_iter().map(|(_, item)| item).collect();
+        let info = self.sampling.info(items.len(), population);
+        (items, info)
+    }
+}
+
+/// Half-width of the 95% confidence interval of the mean, for `values` drawn
+/// at random from a column of about `population` values (finite population
+/// correction included); `None` for fewer than two values
+pub fn mean_margin(values: &[f64], population: usize) -> Option<f64> {
+    let n = values.len();
+    if n < 2 {
+        return None;
+    }
+    let mean = crate::analyze::mean(values);
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let correction = if population > n {
+        ((population - n) as f64 / (population - 1) as f64).sqrt()
+    } else {
+        0.0
+    };
+    Some(1.96 * (variance / n as f64).sqrt() * correction)
+}
+
+impl SampleInfo {
+    /// Whether the rows were drawn at random, so that error bars apply
+    pub fn is_random(&self) -> bool {
+        self.method != SampleMethod::Head
+    }
+
+    /// Error bar (95% margin) of the mean of a column's sampled numeric
+    /// `values`; `None` unless the rows were drawn at random
+    pub fn mean_margin(&self, values: &[f64]) -> Option<f64> {
+        if !self.is_random() {
+            return None;
+        }
+        // The column's share of values in the sample, scaled to the whole source
+        let population = self.population.unwrap_or(self.rows) * values.len() / self.rows.max(1);
+        mean_margin(values, population)
+    }
+
+    /// One sentence for reports, e.g. "Reservoir sample of 1000 of 52311 rows (seed 42)"
+    pub fn description(&self) -> String {
+        let seed = self.seed.map(|s| format!(", seed {}", s)).unwrap_or_default();
+        let Some(population) = self.population else {
+            return format!("First {} rows; the rest was not read", self.rows);
+        };
+        match self.method {
+            SampleMethod::Head => {
+                format!("First {} of {} rows", self.rows, population)
+            }
+            SampleMethod::Reservoir => format!(
+                "Reservoir sample of {} of {} rows ({})",
+                self.rows,
+                population,
+                seed.trim_start_matches(", ")
+            ),
+            SampleMethod::Fraction => format!(
+                "Random sample of {} of {} rows (fraction {}{})",
+                self.rows,
+                population,
+                self.fraction.unwrap_or_default(),
+                seed
+            ),
+            SampleMethod::Stratified => format!(
+                "Sample of {} of {} rows stratified by '{}' ({}{})",
+                self.rows,
+                population,
+                self.stratify_by.as_deref().unwrap_or_default(),
+                match self.fraction {
+                    Some(f) => format!("fraction {}", f),
+                    None => "proportional allocation".to_string(),
+                },
+                seed
+            ),
+        }
+    }
+}
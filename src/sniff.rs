@@ -0,0 +1,242 @@
+// Input format detection from file content: Parquet, Arrow, Avro and workbook magic bytes,
+// JSON vs NDJSON, and the CSV dialect (delimiter, quote character, header, encoding).
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use std::io::Read;
+
+/// Bytes read from the start of a file to detect its format
+pub const SNIFF_LEN: usize = 64 * 1024;
+/// Records parsed when scoring CSV dialects
+const SNIFF_RECORDS: usize = 50;
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+/// Layout of an input file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Csv,
+    Parquet,
+    /// A JSON array of objects
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Excel (XLSX, XLSM, XLSB, XLS) or OpenDocument (ODS) workbook
+    Spreadsheet,
+    /// Arrow IPC file (Feather v2) or stream
+    Arrow,
+    /// Avro object container file
+    Avro,
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" | "tsv" | "txt" => Ok(InputFormat::Csv),
+            "parquet" => Ok(InputFormat::Parquet),
+            "json" => Ok(InputFormat::Json),
+            "ndjson" | "jsonl" => Ok(InputFormat::Ndjson),
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" | "excel" | "spreadsheet" => {
+                Ok(InputFormat::Spreadsheet)
+            }
+            "arrow" | "arrows" | "ipc" | "feather" => Ok(InputFormat::Arrow),
+            "avro" => Ok(InputFormat::Avro),
+            other => Err(format!(
+                "unknown input format '{}' (expected csv, parquet, json, ndjson, xlsx, arrow or avro)",
+                other
+            )),
+        }
+    }
+}
+
+/// How a delimited text file is laid out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub has_headers: bool,
+    pub encoding: &'static Encoding,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: b',',
+            quote: b'"',
+            has_headers: true,
+            encoding: UTF_8,
+        }
+    }
+}
+
+/// Read the first [`SNIFF_LEN`] (decompressed) bytes of a file
+pub fn read_prefix(path: &str) -> std::io::Result<Vec<u8>> {
+    let mut prefix = Vec::with_capacity(SNIFF_LEN);
+    crate::compression::open(path)?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut prefix)?;
+    Ok(prefix)
+}
+
+/// Guess the format of a file from its first bytes; anything that is neither
+/// Parquet, Arrow, Avro, a workbook nor JSON is read as delimited text
+pub fn detect_format(prefix: &[u8]) -> InputFormat {
+    if prefix.starts_with(b"PAR1") {
+        return InputFormat::Parquet;
+    }
+    // An Arrow stream opens with the 0xFFFFFFFF continuation marker of its schema message
+    if prefix.starts_with(b"ARROW1") || prefix.starts_with(&[0xff, 0xff, 0xff, 0xff]) {
+        return InputFormat::Arrow;
+    }
+    if prefix.starts_with(b"Obj\x01") {
+        return InputFormat::Avro;
+    }
+    // XLSX, XLSB and ODS are zip archives; XLS is an OLE compound document
+    if prefix.starts_with(b"PK\x03\x04") || prefix.starts_with(&[0xd0, 0xcf, 0x11, 0xe0]) {
+        return InputFormat::Spreadsheet;
+    }
+    let (encoding, bom_len) = detect_encoding(prefix);
+    let (text, _) = encoding.decode_without_bom_handling(&prefix[bom_len..]);
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') {
+        return InputFormat::Json;
+    }
+    if trimmed.starts_with('{') {
+        // NDJSON when the first line is a complete object on its own
+        let first_line = trimmed.lines().next().unwrap_or("");
+        return match serde_json::from_str::<serde_json::Value>(first_line) {
+            Ok(serde_json::Value::Object(_)) => InputFormat::Ndjson,
+            _ => InputFormat::Json,
+        };
+    }
+    InputFormat::Csv
+}
+
+/// Encoding of a text file and the length of its byte order mark: a BOM wins,
+/// then valid UTF-8, falling back to Windows-1252 (a superset of Latin-1)
+pub fn detect_encoding(prefix: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(prefix) {
+        return (encoding, bom_len);
+    }
+    match std::str::from_utf8(prefix) {
+        Ok(_) => (UTF_8, 0),
+        // A multi-byte character cut off by the end of a full prefix is still UTF-8
+        Err(e) if e.error_len().is_none() && prefix.len() >= SNIFF_LEN => (UTF_8, 0),
+        Err(_) => (WINDOWS_1252, 0),
+    }
+}
+
+/// Detect the delimiter, quote character, header row and encoding of delimited text
+pub fn sniff_csv_dialect(prefix: &[u8]) -> CsvDialect {
+    let (encoding, bom_len) = detect_encoding(prefix);
+    let (text, _) = encoding.decode_without_bom_handling(&prefix[bom_len..]);
+    CsvDialect {
+        encoding,
+        ..sniff_csv_text(&text, prefix.len() >= SNIFF_LEN)
+    }
+}
+
+/// Detect the delimiter, quote character and header row of decoded text;
+/// `truncated` when the text was cut from a longer file and may end mid-line
+pub fn sniff_csv_text(text: &str, truncated: bool) -> CsvDialect {
+    let mut text = text;
+    if truncated && let Some(end) = text.rfind('\n') {
+        text = &text[..=end];
+    }
+    let quote = sniff_quote(text);
+    let delimiter = sniff_delimiter(text, quote);
+    let records = parse_records(text, delimiter, quote);
+    CsvDialect {
+        delimiter,
+        quote,
+        has_headers: sniff_header(&records),
+        encoding: UTF_8,
+    }
+}
+
+/// `'` only when it opens more fields than `"` does
+fn sniff_quote(text: &str) -> u8 {
+    let opens = |q: char| {
+        text.lines()
+            .take(SNIFF_RECORDS)
+            .map(|line| {
+                let mut count = usize::from(line.starts_with(q));
+                for d in DELIMITERS {
+                    count += line.matches(&format!("{}{}", d as char, q)).count();
+                }
+                count
+            })
+            .sum::<usize>()
+    };
+    if opens('\'') > opens('"') { b'\'' } else { b'"' }
+}
+
+/// The candidate splitting most records into the same number (> 1) of fields;
+/// ties go to the candidate giving more fields, then to the earlier candidate
+fn sniff_delimiter(text: &str, quote: u8) -> u8 {
+    let mut best = (b',', 0.0, 0);
+    for delimiter in DELIMITERS {
+        let records = parse_records(text, delimiter, quote);
+        if records.is_empty() {
+            continue;
+        }
+        let mut counts = std::collections::HashMap::new();
+        for record in &records {
+            *counts.entry(record.len()).or_insert(0usize) += 1;
+        }
+        let (fields, hits) = counts
+            .into_iter()
+            .max_by_key(|&(fields, hits)| (hits, fields))
+            .unwrap_or((0, 0));
+        if fields < 2 {
+            continue;
+        }
+        let consistency = hits as f64 / records.len() as f64;
+        if consistency > best.1 || (consistency == best.1 && fields > best.2) {
+            best = (delimiter, consistency, fields);
+        }
+    }
+    best.0
+}
+
+fn parse_records(text: &str, delimiter: u8, quote: u8) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .records()
+        .take(SNIFF_RECORDS)
+        .filter_map(Result::ok)
+        .map(|r| r.iter().map(|s| s.to_string()).collect())
+        .collect()
+}
+
+/// A first row is a header when its cells are not numbers in columns whose
+/// other cells are; with no numeric column to judge by a header is assumed,
+/// unless the first row repeats a value or has an empty cell
+fn sniff_header(records: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = records.split_first() else {
+        return true;
+    };
+    let is_number = |v: &str| v.trim().parse::<f64>().is_ok();
+    let mut votes = 0i32;
+    for (i, cell) in first.iter().enumerate() {
+        let values: Vec<&str> = rest
+            .iter()
+            .filter_map(|r| r.get(i))
+            .map(String::as_str)
+            .filter(|v| !v.trim().is_empty())
+            .collect();
+        if !values.is_empty() && values.iter().all(|v| is_number(v)) {
+            votes += if is_number(cell) { -1 } else { 1 };
+        }
+    }
+    if votes != 0 {
+        return votes > 0;
+    }
+    let mut seen = std::collections::HashSet::new();
+    first
+        .iter()
+        .all(|cell| !cell.trim().is_empty() && seen.insert(cell.as_str()))
+}
@@ -0,0 +1,139 @@
+// Template-driven report rendering: reports are produced by rendering a
+// template (minijinja syntax) against a structured profile context.
+use crate::analyze::{ColumnProfile, Correlation, Profile, ReportSection, SampleSummary};
+use minijinja::{AutoEscape, Environment};
+use serde::Serialize;
+
+/// Default HTML report template
+pub const DEFAULT_HTML_TEMPLATE: &str = include_str!("../templates/report.html");
+/// Default Markdown report template
+pub const DEFAULT_MARKDOWN_TEMPLATE: &str = include_str!("../templates/report.md");
+
+/// Output flavour of a template, which decides how values are escaped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateKind {
+    Html,
+    Markdown,
+}
+
+/// Everything a report template can refer to
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportContext {
+    pub title: String,
+    pub file: String,
+    pub generated_at: String,
+    pub css: String,
+    /// The sample the statistics were computed from, when they were
+    pub sample: Option<SampleSummary>,
+    pub correlations: Vec<Correlation>,
+    /// Analyzer findings about the whole dataset
+    pub sections: Vec<ReportSection>,
+    pub columns: Vec<ColumnContext>,
+    /// One entry per sheet for multi-sheet workbooks, whose `correlations`
+    /// and `columns` are then empty
+    pub sheets: Vec<SheetContext>,
+}
+
+/// The report sections of one workbook sheet
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetContext {
+    pub name: String,
+    pub sample: Option<SampleSummary>,
+    pub correlations: Vec<Correlation>,
+    pub sections: Vec<ReportSection>,
+    pub columns: Vec<ColumnContext>,
+}
+
+/// One report section: a column's analysis and its charts
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnContext {
+    #[serde(flatten)]
+    pub profile: ColumnProfile,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub charts: Vec<ChartContext>,
+}
+
+/// A rendered chart: either a link to an image file or inline SVG markup
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartContext {
+    pub kind: String,
+    pub alt: String,
+    pub src: Option<String>,
+    pub svg: Option<String>,
+}
+
+/// The context a report template is rendered from: the profile's statistics,
+/// without charts yet
+pub fn build_context(profile: &Profile) -> ReportContext {
+    ReportContext {
+        title: "Data Storytelling Report".to_string(),
+        file: profile.source.clone(),
+        generated_at: profile.generated_at.clone(),
+        css: String::new(),
+        sample: profile.sample.clone(),
+        correlations: profile.correlations.clone(),
+        sections: profile.sections.clone(),
+        columns: column_contexts(profile),
+        sheets: Vec::new(),
+    }
+}
+
+/// The report sections of a workbook sheet
+pub fn sheet_context(name: &str, profile: &Profile) -> SheetContext {
+    SheetContext {
+        name: name.to_string(),
+        sample: profile.sample.clone(),
+        correlations: profile.correlations.clone(),
+        sections: profile.sections.clone(),
+        columns: column_contexts(profile),
+    }
+}
+
+fn column_contexts(profile: &Profile) -> Vec<ColumnContext> {
+    profile
+        .columns
+        .iter()
+        .map(|column| ColumnContext {
+            profile: column.clone(),
+            charts: Vec::new(),
+        })
+        .collect()
+}
+
+/// Render a report template against a context (usually a [`ReportContext`]).
+///
+/// HTML templates auto-escape every value; use the `safe` filter only for
+/// trusted markup such as `css` or inline chart SVG. A `fixed(n)` filter
+/// formats numbers with `n` decimals (2 by default).
+pub fn render<C: Serialize>(
+    source: &str,
+    kind: TemplateKind,
+    context: &C,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut env = Environment::new();
+    env.set_auto_escape_callback(move |_| match kind {
+        TemplateKind::Html => AutoEscape::Html,
+        TemplateKind::Markdown => AutoEscape::None,
+    });
+    env.add_filter("fixed", |value: f64, decimals: Option<usize>| {
+        format!("{:.*}", decimals.unwrap_or(2), value)
+    });
+    env.add_template("report", source)?;
+    Ok(env.get_template("report")?.render(context)?)
+}
+
+/// Load a user-supplied template file, falling back to the built-in default
+pub fn load_template(
+    template: Option<&str>,
+    kind: TemplateKind,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match template {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read template '{}': {}", path, e).into()),
+        None => Ok(match kind {
+            TemplateKind::Html => DEFAULT_HTML_TEMPLATE,
+            TemplateKind::Markdown => DEFAULT_MARKDOWN_TEMPLATE,
+        }
+        .to_string()),
+    }
+}
@@ -0,0 +1,284 @@
+// Data quality assertions: rules declared in a TOML or YAML file are checked
+// against a dataset, producing a pass/fail report with the failing rows.
+use crate::analyze::{infer_column_type, is_missing};
+use crate::error::DataError;
+use crate::formats::{Dataset, read_dataset};
+use crate::templates::{TemplateKind, render};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Default HTML validation report template
+pub const DEFAULT_VALIDATION_HTML_TEMPLATE: &str = include_str!("../templates/validate.html");
+/// Default Markdown validation report template
+pub const DEFAULT_VALIDATION_MARKDOWN_TEMPLATE: &str = include_str!("../templates/validate.md");
+
+/// Failing rows kept per check; the total count is always reported
+const MAX_FAILING_ROWS: usize = 100;
+
+/// A rules file: a list of rules, each naming a column and one or more checks
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+/// Checks on one column. Value checks (`between`, `min`, `max`, `matches`,
+/// `in`) skip missing cells; use `not_null` or `max_null_rate` for those.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub column: String,
+    /// Inclusive numeric range, e.g. `between = [0, 120]`
+    pub between: Option<[f64; 2]>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Regular expression every value must match as a whole
+    pub matches: Option<String>,
+    /// Allowed values
+    #[serde(rename = "in")]
+    pub allowed: Option<Vec<String>>,
+    pub unique: Option<bool>,
+    pub not_null: Option<bool>,
+    /// Highest acceptable share of missing cells, between 0 and 1
+    pub max_null_rate: Option<f64>,
+    /// Expected column type: numeric, categorical, boolean or date
+    #[serde(rename = "type")]
+    pub col_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub source: String,
+    pub row_count: usize,
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub column: String,
+    /// Human-readable description of the check, e.g. "between 0 and 120"
+    pub check: String,
+    pub passed: bool,
+    pub message: String,
+    /// Number of failing rows (may exceed `failing_rows.len()`)
+    pub failing_count: usize,
+    pub failing_rows: Vec<FailingRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailingRow {
+    /// 1-based data row number, not counting the header
+    pub row: usize,
+    pub value: String,
+}
+
+impl ValidationReport {
+    pub fn failed_checks(&self) -> usize {
+        self.checks.iter().filter(|c| !c.passed).count()
+    }
+}
+
+/// Parse a rules file; `.yaml` / `.yml` files are read as YAML, anything else as TOML
+pub fn load_rules(path: &str) -> Result<RuleSet, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read rules file '{}': {}", path, e))?;
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let rules: RuleSet = match ext {
+        "yaml" | "yml" => serde_yaml::from_str(&content)?,
+        _ => toml::from_str(&content)?,
+    };
+    Ok(rules)
+}
+
+/// Read a data file and a rules file and evaluate every rule
+pub fn validate_file(
+    path: &str,
+    rules_path: &str,
+) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    let rules = load_rules(rules_path)?;
+    let dataset = read_dataset(path)?;
+    validate_dataset(&dataset, &rules, path)
+}
+
+/// Evaluate every check of every rule against a dataset
+pub fn validate_dataset(
+    dataset: &Dataset,
+    rules: &RuleSet,
+    source: &str,
+) -> Result<ValidationReport, Box<dyn std::error::Error>> {
+    let mut checks = Vec::new();
+    for rule in &rules.rules {
+        let Some(values) = dataset.column(&rule.column) else {
+            checks.push(CheckResult {
+                column: rule.column.clone(),
+                check: "column exists".to_string(),
+                passed: false,
+                message: format!("Column '{}' not found", rule.column),
+                failing_count: 0,
+                failing_rows: Vec::new(),
+            });
+            continue;
+        };
+        if let Some(expected) = &rule.col_type {
+            let actual = infer_column_type(&values);
+            checks.push(CheckResult {
+                column: rule.column.clone(),
+                check: format!("type is {}", expected),
+                passed: actual == expected,
+                message: format!("Detected type: {}", actual),
+                failing_count: 0,
+                failing_rows: Vec::new(),
+            });
+        }
+        if rule.not_null == Some(true) {
+            checks.push(row_check(&rule.column, "not null", &values, |v| !is_missing(v), true));
+        }
+        if let Some(limit) = rule.max_null_rate {
+            let nulls = values.iter().filter(|v| is_missing(v)).count();
+            let rate = if values.is_empty() {
+                0.0
+            } else {
+                nulls as f64 / values.len() as f64
+            };
+            checks.push(CheckResult {
+                column: rule.column.clone(),
+                check: format!("null rate at most {}%", limit * 100.0),
+                passed: rate <= limit,
+                message: format!("Null rate: {:.2}% ({} of {} rows)", rate * 100.0, nulls, values.len()),
+                failing_count: 0,
+                failing_rows: Vec::new(),
+            });
+        }
+        let (low, high) = match rule.between {
+            Some([low, high]) => (Some(low), Some(high)),
+            None => (rule.min, rule.max),
+        };
+        if low.is_some() || high.is_some() {
+            let description = match (low, high) {
+                (Some(l), Some(h)) => format!("between {} and {}", l, h),
+                (Some(l), None) => format!("at least {}", l),
+                (None, Some(h)) => format!("at most {}", h),
+                (None, None) => unreachable!(),
+            };
+            let in_range = |v: &str| match v.trim().parse::<f64>() {
+                Ok(n) => low.is_none_or(|l| n >= l) && high.is_none_or(|h| n <= h),
+                Err(_) => false,
+            };
+            checks.push(row_check(&rule.column, &description, &values, in_range, false));
+        }
+        if let Some(pattern) = &rule.matches {
+            // The pattern must match the whole value, not just part of it
+            let re = regex::Regex::new(&format!("^(?:{})$", pattern))
+                .map_err(|e| format!("Invalid regex for column '{}': {}", rule.column, e))?;
+            checks.push(row_check(
+                &rule.column,
+                &format!("matches /{}/", pattern),
+                &values,
+                |v| re.is_match(v),
+                false,
+            ));
+        }
+        if let Some(allowed) = &rule.allowed {
+            checks.push(row_check(
+                &rule.column,
+                &format!("in [{}]", allowed.join(", ")),
+                &values,
+                |v| allowed.iter().any(|a| a == v),
+                false,
+            ));
+        }
+        if rule.unique == Some(true) {
+            let mut seen: HashMap<&str, usize> = HashMap::new();
+            let mut failing = Vec::new();
+            for (i, v) in values.iter().enumerate() {
+                if is_missing(v) {
+                    continue;
+                }
+                let count = seen.entry(v.as_str()).or_insert(0);
+                *count += 1;
+                if *count > 1 {
+                    failing.push(FailingRow {
+                        row: i + 1,
+                        value: v.clone(),
+                    });
+                }
+            }
+            checks.push(result_from_failures(&rule.column, "unique", failing, "duplicate values"));
+        }
+    }
+    Ok(ValidationReport {
+        source: source.to_string(),
+        row_count: dataset.rows.len(),
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+    })
+}
+
+/// Check every cell with `ok`; missing cells are skipped unless `include_missing`
+fn row_check(
+    column: &str,
+    description: &str,
+    values: &[String],
+    ok: impl Fn(&str) -> bool,
+    include_missing: bool,
+) -> CheckResult {
+    let failing = values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| include_missing || !is_missing(v))
+        .filter(|(_, v)| !ok(v))
+        .map(|(i, v)| FailingRow {
+            row: i + 1,
+            value: v.clone(),
+        })
+        .collect();
+    result_from_failures(column, description, failing, "failing rows")
+}
+
+fn result_from_failures(
+    column: &str,
+    description: &str,
+    mut failing: Vec<FailingRow>,
+    what: &str,
+) -> CheckResult {
+    let failing_count = failing.len();
+    failing.truncate(MAX_FAILING_ROWS);
+    CheckResult {
+        column: column.to_string(),
+        check: description.to_string(),
+        passed: failing_count == 0,
+        message: if failing_count == 0 {
+            "OK".to_string()
+        } else {
+            format!("{} {}", failing_count, what)
+        },
+        failing_count,
+        failing_rows: failing,
+    }
+}
+
+/// Write the validation report as HTML, Markdown (`md`) or JSON
+pub fn generate_validation_report(
+    report: &ValidationReport,
+    report_name: &str,
+    format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = match format {
+        "html" => render(DEFAULT_VALIDATION_HTML_TEMPLATE, TemplateKind::Html, report)?,
+        "md" | "markdown" => {
+            render(DEFAULT_VALIDATION_MARKDOWN_TEMPLATE, TemplateKind::Markdown, report)?
+        }
+        "json" => serde_json::to_string_pretty(report)? + "\n",
+        other => {
+            let message = format!("Unsupported validation report format '{}'", other);
+            return Err(DataError::UnsupportedFormat(message).into());
+        }
+    };
+    let mut file = crate::report::create_report_file(report_name)?;
+    file.write_all(output.as_bytes())?;
+    Ok(())
+}
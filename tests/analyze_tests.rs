@@ -1,7 +1,10 @@
 // Unit tests for analysis functions
 use datastory::analyze::{
-    detect_column_type, mean, median, mode, pearson_correlation, quartiles,
+    column_cleaning_suggestions, correlation_pairs, detect_column_type, mean, median, mode,
+    pearson_correlation, profile, quartiles,
 };
+use datastory::formats::Dataset;
+use datastory::sample::{SampleMethod, Sampling};
 
 #[cfg(test)]
 mod tests {
@@ -29,6 +32,8 @@ mod tests {
     fn test_mode() {
         let data = vec!["a".to_string(), "b".to_string(), "a".to_string()];
         assert_eq!(mode(&data), "a");
+        let tied: Vec<String> = ["y", "x", "x", "y"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(mode(&tied), "y");
     }
 
     #[test]
@@ -58,4 +63,78 @@ mod tests {
         let corr = pearson_correlation(&x, &y).unwrap();
         assert!((corr - 1.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_column_cleaning_suggestions() {
+        let values: Vec<String> = ["1", "2", "", "n/a", "3", "4"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            column_cleaning_suggestions(&values),
+            vec![
+                "1 missing values detected",
+                "1 non-numeric values in a mostly numeric column"
+            ]
+        );
+        let clean: Vec<String> = ["x", "y"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(column_cleaning_suggestions(&clean), vec!["No cleaning needed"]);
+    }
+
+    #[test]
+    fn test_correlation_pairs_in_order() {
+        // Column k is 1, 2, 3, 4 scaled by k + 1, except the constant and empty columns
+        let mut columns: Vec<Vec<f64>> = (0..40)
+            .map(|k| (1..=4).map(|v| (v * (k + 1)) as f64).collect())
+            .collect();
+        columns[3] = vec![5.0; 4];
+        columns[7] = Vec::new();
+        let pairs = correlation_pairs(&columns);
+        let expected: Vec<(usize, usize)> = (0..40)
+            .flat_map(|i| ((i + 1)..40).map(move |j| (i, j)))
+            .filter(|&(i, j)| ![3, 7].contains(&i) && ![3, 7].contains(&j))
+            .collect();
+        let got: Vec<(usize, usize)> = pairs.iter().map(|&(i, j, _)| (i, j)).collect();
+        assert_eq!(got, expected);
+        assert!(pairs.iter().all(|&(_, _, r)| (r - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_profile_of_dataset() {
+        let dataset = Dataset {
+            headers: vec!["amount".to_string(), "city".to_string(), "mixed".to_string()],
+            rows: (0..100)
+                .map(|i| {
+                    vec![
+                        (i % 10).to_string(),
+                        ["Paris", "Lyon", "Paris"][i % 3].to_string(),
+                        if i % 2 == 0 { i.to_string() } else { "n/a".to_string() },
+                    ]
+                })
+                .collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let whole = profile(&dataset).with_source("sales.csv");
+        assert_eq!(whole.source, "sales.csv");
+        assert_eq!((whole.row_count, whole.column_count), (100, 3));
+        assert!(whole.sample.is_none());
+        // Numeric parts first, then the non-numeric ones, each in column order
+        let sections: Vec<(usize, bool)> =
+            whole.columns.iter().map(|c| (c.index, c.numeric.is_some())).collect();
+        assert_eq!(sections, vec![(0, true), (2, true), (1, false), (2, false)]);
+        let amount = whole.columns[0].numeric.as_ref().unwrap();
+        assert_eq!((amount.min, amount.max, amount.mean, amount.unique), (0.0, 9.0, 4.5, 10));
+        assert!(amount.mean_margin.is_none());
+        let city = whole.columns[2].categorical.as_ref().unwrap();
+        assert_eq!((city.mode.as_str(), city.mode_count, city.unique), ("Paris", 67, 2));
+        // Only half of 'mixed' is numeric, so it does not pair with 'amount'
+        assert!(whole.correlations.is_empty());
+
+        let sampling = Sampling::new(Some(SampleMethod::Reservoir), Some(30), None, None, None)
+            .unwrap()
+            .unwrap();
+        let sampled = profile(&sampling.apply(dataset).unwrap());
+        assert_eq!(sampled.row_count, 30);
+        assert_eq!(sampled.sample.as_ref().unwrap().info.population, Some(100));
+        assert!(sampled.columns[0].numeric.as_ref().unwrap().mean_margin.unwrap() > 0.0);
+    }
 }
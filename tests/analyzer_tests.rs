@@ -0,0 +1,159 @@
+// Unit tests for pluggable analyzers
+use datastory::analyze::{self, ColumnProfile, Profile, ReportSection};
+use datastory::analyzer::{
+    self, Analyzer, AnalyzerRegistry, ColumnValues, CorrelationAnalyzer, DatasetColumns,
+    StatisticsAnalyzer, TypedColumn,
+};
+use datastory::formats::Dataset;
+use datastory::report::{HtmlOptions, generate_report};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flags text values that are not ISO 3166 alpha-2 country codes, in
+    /// columns named `country`
+    struct CountryCodeAnalyzer;
+
+    impl Analyzer for CountryCodeAnalyzer {
+        fn name(&self) -> &str {
+            "country-codes"
+        }
+
+        fn analyze_column(&self, column: &TypedColumn, profile: &mut ColumnProfile) {
+            let ColumnValues::Text(values) = column.values else {
+                return;
+            };
+            if column.name != "country" {
+                return;
+            }
+            let invalid: Vec<String> = values
+                .iter()
+                .filter(|v| v.len() != 2 || !v.chars().all(|c| c.is_ascii_uppercase()))
+                .cloned()
+                .collect();
+            if !invalid.is_empty() {
+                profile.suggestions.push("Normalise country names to ISO codes".to_string());
+                profile.sections.push(ReportSection {
+                    analyzer: self.name().to_string(),
+                    title: "Invalid country codes".to_string(),
+                    items: invalid,
+                });
+            }
+        }
+
+        fn analyze_dataset(&self, dataset: &DatasetColumns, profile: &mut Profile) {
+            if dataset.headers.iter().any(|h| h == "country") {
+                profile.sections.push(ReportSection {
+                    analyzer: self.name().to_string(),
+                    title: "Country check".to_string(),
+                    items: vec!["Checked against ISO 3166".to_string()],
+                });
+            }
+        }
+    }
+
+    fn dataset() -> Dataset {
+        Dataset {
+            headers: vec!["amount".to_string(), "country".to_string()],
+            rows: vec![
+                vec!["1".to_string(), "FR".to_string()],
+                vec!["2".to_string(), "France".to_string()],
+                vec!["3".to_string(), "DE".to_string()],
+            ],
+            types: None,
+            numbers: None,
+            sample: None,
+        }
+    }
+
+    #[test]
+    fn test_built_in_analyzers() {
+        assert_eq!(
+            AnalyzerRegistry::default().names(),
+            vec!["statistics", "outliers", "correlations"]
+        );
+
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(CorrelationAnalyzer);
+        registry.register(StatisticsAnalyzer);
+        // Registering a name again replaces the analyzer in place
+        registry.register(CorrelationAnalyzer);
+        assert_eq!(registry.names(), vec!["correlations", "statistics"]);
+        assert_eq!(registry.unregister("correlations"), Ok(true));
+        assert_eq!(registry.unregister("correlations"), Ok(false));
+        assert_eq!(registry.names(), vec!["statistics"]);
+    }
+
+    #[test]
+    fn test_statistics_cannot_be_unregistered() {
+        assert!(analyzer::unregister("statistics").is_err());
+        assert!(analyzer::analyzer_names().contains(&"statistics".to_string()));
+        let mut registry = AnalyzerRegistry::default();
+        assert!(registry.unregister("statistics").is_err());
+        assert_eq!(registry.names()[0], "statistics");
+
+        // Reports still render with every statistic after the attempt
+        let options = HtmlOptions {
+            charts: false,
+            ..HtmlOptions::default()
+        };
+        let out = std::env::temp_dir().join("datastory_analyzer_statistics.html");
+        generate_report(&dataset(), "data.csv", out.to_str().unwrap(), "html", &options).unwrap();
+        let html = std::fs::read_to_string(&out).unwrap();
+        assert!(html.contains("amount") && html.contains("Mean"));
+    }
+
+    #[test]
+    fn test_typed_columns() {
+        let headers = vec!["a".to_string(), "b".to_string()];
+        let num = vec![vec![1.0, 2.0], vec![]];
+        let cat = vec![vec!["x".to_string()], vec!["true".to_string(), "false".to_string()]];
+        let dataset = DatasetColumns {
+            headers: &headers,
+            num_columns: &num,
+            cat_columns: &cat,
+            types: None,
+            sample: None,
+        };
+        let columns: Vec<(&str, &str, usize)> = dataset
+            .columns()
+            .iter()
+            .map(|c| (c.name, c.col_type, c.values.len()))
+            .collect();
+        assert_eq!(columns, vec![("a", "numeric", 2), ("a", "categorical", 1), ("b", "boolean", 2)]);
+    }
+
+    #[test]
+    fn test_registered_analyzer_adds_sections_to_reports() {
+        analyzer::register(CountryCodeAnalyzer);
+        assert!(analyzer::analyzer_names().contains(&"country-codes".to_string()));
+
+        let profile = analyze::profile(&dataset());
+        let country = profile.columns.iter().find(|c| c.name == "country").unwrap();
+        assert_eq!(country.sections.len(), 1);
+        assert_eq!(country.sections[0].items, vec!["France"]);
+        assert!(country.suggestions.contains(&"Normalise country names to ISO codes".to_string()));
+        assert_eq!(profile.sections[0].title, "Country check");
+        // The built-in analyses still ran
+        assert_eq!(profile.columns[0].numeric.as_ref().unwrap().mean, 2.0);
+
+        let options = HtmlOptions {
+            charts: false,
+            ..HtmlOptions::default()
+        };
+        let dir = std::env::temp_dir();
+        for (format, expected) in [
+            ("md", "Invalid country codes"),
+            ("html", "<h3>Invalid country codes</h3>"),
+            ("interactive", "<h3>Country check</h3>"),
+            ("json", "\"analyzer\": \"country-codes\""),
+        ] {
+            let out = dir.join(format!("datastory_analyzer.{}", format));
+            generate_report(&dataset(), "data.csv", out.to_str().unwrap(), format, &options).unwrap();
+            let report = std::fs::read_to_string(&out).unwrap();
+            assert!(report.contains(expected), "{} report lacks {}", format, expected);
+            assert!(report.contains("France"), "{} report lacks the invalid code", format);
+        }
+    }
+}
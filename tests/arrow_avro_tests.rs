@@ -0,0 +1,236 @@
+// Unit tests for the Arrow IPC and Avro readers
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, RecordBatch,
+    StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use avro_schema::schema::{Field as AvroField, IntLogical, Record, Schema as AvroSchema};
+use avro_schema::write::encode::zigzag_encode;
+use datastory::formats::{ColumnType, Numbers, read_dataset};
+use datastory::sniff::{InputFormat, detect_format, read_prefix};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("amount", DataType::Float64, true),
+            Field::new("closed", DataType::Boolean, false),
+            Field::new("day", DataType::Date32, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec![Some("north"), None])),
+            Arc::new(Float64Array::from(vec![Some(1200.5), Some(80.0)])),
+            Arc::new(BooleanArray::from(vec![true, false])),
+            // 2024-01-01 and 2024-01-02
+            Arc::new(Date32Array::from(vec![19723, 19724])),
+        ];
+        RecordBatch::try_new(Arc::new(schema), columns).unwrap()
+    }
+
+    #[test]
+    fn test_read_arrow_file_and_stream() {
+        let batch = sales_batch();
+        let file_path = std::env::temp_dir().join("datastory_sales.arrow");
+        let mut writer = arrow_ipc::writer::FileWriter::try_new(
+            std::fs::File::create(&file_path).unwrap(),
+            &batch.schema(),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        let stream_path = std::env::temp_dir().join("datastory_sales.arrows");
+        let mut writer = arrow_ipc::writer::StreamWriter::try_new(
+            std::fs::File::create(&stream_path).unwrap(),
+            &batch.schema(),
+        )
+        .unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+
+        for path in [file_path, stream_path] {
+            let path = path.to_str().unwrap();
+            assert_eq!(detect_format(&read_prefix(path).unwrap()), InputFormat::Arrow);
+            let dataset = read_dataset(path).unwrap();
+            assert_eq!(dataset.headers, vec!["region", "amount", "closed", "day"]);
+            // Numeric columns are read as numbers, not written into the rows
+            assert_eq!(dataset.rows[0], vec!["north", "", "true", "2024-01-01"]);
+            assert_eq!(dataset.rows[1], vec!["", "", "false", "2024-01-02"]);
+            let amount = Numbers::Float(vec![Some(1200.5), Some(80.0)]);
+            assert_eq!(dataset.numbers(1), Some(&amount));
+            assert_eq!(dataset.column("amount").unwrap(), vec!["1200.5", "80.0"]);
+            let day = dataset.column("day").unwrap();
+            assert_eq!(datastory::analyze::infer_column_type(&day), "date");
+        }
+    }
+
+    #[test]
+    fn test_arrow_c_stream_to_dataset() {
+        use arrow_array::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+        let batch = sales_batch();
+        let reader = arrow_array::RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        // What a DataFrame's `__arrow_c_stream__` capsule holds
+        let mut stream = FFI_ArrowArrayStream::new(Box::new(reader));
+        let reader = unsafe { ArrowArrayStreamReader::from_raw(&mut stream) }.unwrap();
+        let dataset = datastory::formats::arrow_dataset(reader).unwrap();
+        assert_eq!(dataset.headers, vec!["region", "amount", "closed", "day"]);
+        assert_eq!(dataset.rows.len(), 2);
+        assert_eq!(dataset.rows[1], vec!["", "", "false", "2024-01-02"]);
+        assert_eq!(dataset.column("amount").unwrap(), vec!["1200.5", "80.0"]);
+    }
+
+    #[test]
+    fn test_schema_types_are_kept() {
+        let schema = Schema::new(vec![
+            Field::new("code", DataType::Utf8, false),
+            Field::new("units", DataType::Int64, true),
+            Field::new("ratio", DataType::Float64, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec!["00123", "00456", "00789"])),
+            Arc::new(Int64Array::from(vec![Some(3), None, Some(5)])),
+            Arc::new(Float64Array::from(vec![0.5, f64::NAN, 1.5])),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), columns).unwrap();
+        let reader = arrow_array::RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let dataset = datastory::formats::arrow_dataset(reader).unwrap();
+        let types = [ColumnType::Text, ColumnType::Numeric, ColumnType::Numeric];
+        assert_eq!(dataset.types.as_deref(), Some(&types[..]));
+
+        let profile = datastory::analyze::profile(&dataset);
+        let columns: Vec<(&str, &str, usize)> = profile
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.col_type.as_str(), c.count))
+            .collect();
+        // The nulls of "units" and the NaN of "ratio" are missing values, not
+        // a non-numeric part of their own
+        assert_eq!(
+            columns,
+            vec![("units", "numeric", 2), ("ratio", "numeric", 2), ("code", "categorical", 3)]
+        );
+        assert_eq!(profile.columns[1].numeric.as_ref().unwrap().median, 1.0);
+    }
+
+    #[test]
+    fn test_numbers_are_read_exactly() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("price", DataType::Decimal128(25, 2), false),
+            Field::new("shop", DataType::Utf8, false),
+        ]);
+        let prices = Decimal128Array::from(vec![12345678901234567890123, -5, 700])
+            .with_precision_and_scale(25, 2)
+            .unwrap();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![9007199254740993, 2, 3])),
+            Arc::new(prices),
+            Arc::new(StringArray::from(vec!["a", "b", "a"])),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), columns).unwrap();
+        let reader = arrow_array::RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        let dataset = datastory::formats::arrow_dataset(reader).unwrap();
+        assert_eq!(dataset.column("id").unwrap(), vec!["9007199254740993", "2", "3"]);
+        let prices = dataset.column("price").unwrap();
+        assert_eq!(prices, vec!["123456789012345678901.23", "-0.05", "7.00"]);
+        let (num_columns, _) = dataset.split_columns();
+        assert_eq!(num_columns[1][1..], [-0.05, 7.0]);
+
+        // Samples and selections keep the numbers with their rows
+        let shop = Some("shop".to_string());
+        let sampling = datastory::sample::Sampling::new(None, Some(2), None, shop, None)
+            .unwrap()
+            .unwrap();
+        let sample = sampling.apply(dataset.clone()).unwrap();
+        let ids = sample.column("id").unwrap();
+        let shops = sample.column("shop").unwrap();
+        assert_eq!(ids.len(), 2);
+        assert!(shops.contains(&"b".to_string()));
+        for (id, shop) in ids.iter().zip(&shops) {
+            let row = dataset.column("id").unwrap().iter().position(|v| v == id).unwrap();
+            assert_eq!(&dataset.column("shop").unwrap()[row], shop);
+        }
+        let selected = dataset.select_columns(&["price".to_string()], &[]).unwrap();
+        assert_eq!(selected.column("price").unwrap()[2], "7.00");
+    }
+
+    fn write_string(value: &str, data: &mut Vec<u8>) {
+        zigzag_encode(value.len() as i64, data).unwrap();
+        data.extend_from_slice(value.as_bytes());
+    }
+
+    #[test]
+    fn test_read_avro_container_file() {
+        let record = Record::new(
+            "sale",
+            vec![
+                AvroField::new(
+                    "region",
+                    AvroSchema::Union(vec![AvroSchema::Null, AvroSchema::String(None)]),
+                ),
+                AvroField::new("amount", AvroSchema::Double),
+                AvroField::new("units", AvroSchema::Long(None)),
+                AvroField::new("day", AvroSchema::Int(Some(IntLogical::Date))),
+                AvroField::new("tags", AvroSchema::Array(Box::new(AvroSchema::String(None)))),
+            ],
+        );
+        let mut data = Vec::new();
+        // north, 1200.5, 3, 2024-01-01, ["a", "b"]
+        zigzag_encode(1, &mut data).unwrap();
+        write_string("north", &mut data);
+        data.extend_from_slice(&1200.5f64.to_le_bytes());
+        zigzag_encode(3, &mut data).unwrap();
+        zigzag_encode(19723, &mut data).unwrap();
+        zigzag_encode(2, &mut data).unwrap();
+        write_string("a", &mut data);
+        write_string("b", &mut data);
+        zigzag_encode(0, &mut data).unwrap();
+        // null, -2.0, -7, 2024-01-02, []
+        zigzag_encode(0, &mut data).unwrap();
+        data.extend_from_slice(&(-2.0f64).to_le_bytes());
+        zigzag_encode(-7, &mut data).unwrap();
+        zigzag_encode(19724, &mut data).unwrap();
+        zigzag_encode(0, &mut data).unwrap();
+
+        let mut file = Vec::new();
+        avro_schema::write::write_metadata(&mut file, record, None).unwrap();
+        let block = avro_schema::file::CompressedBlock::new(2, data);
+        avro_schema::write::write_block(&mut file, &block).unwrap();
+        let path = std::env::temp_dir().join("datastory_sales.avro");
+        std::fs::write(&path, file).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(detect_format(&read_prefix(path).unwrap()), InputFormat::Avro);
+        let dataset = read_dataset(path).unwrap();
+        assert_eq!(dataset.headers, vec!["region", "amount", "units", "day", "tags"]);
+        assert_eq!(dataset.rows[0], vec!["north", "", "", "2024-01-01", r#"["a","b"]"#]);
+        assert_eq!(dataset.rows[1], vec!["", "", "", "2024-01-02", "[]"]);
+        assert_eq!(dataset.numbers(2), Some(&Numbers::Int(vec![Some(3), Some(-7)])));
+        assert_eq!(dataset.column("amount").unwrap(), vec!["1200.5", "-2.0"]);
+        assert_eq!(datastory::analyze::infer_column_type(&dataset.column("day").unwrap()), "date");
+        let types = [
+            ColumnType::Text,
+            ColumnType::Numeric,
+            ColumnType::Numeric,
+            ColumnType::Date,
+            ColumnType::Text,
+        ];
+        assert_eq!(dataset.types.as_deref(), Some(&types[..]));
+    }
+
+    #[test]
+    fn test_truncated_avro_is_an_error() {
+        let record = Record::new("r", vec![AvroField::new("n", AvroSchema::Long(None))]);
+        let mut file = Vec::new();
+        avro_schema::write::write_metadata(&mut file, record, None).unwrap();
+        // Two rows announced, one encoded
+        let block = avro_schema::file::CompressedBlock::new(2, vec![2]);
+        avro_schema::write::write_block(&mut file, &block).unwrap();
+        let path = std::env::temp_dir().join("datastory_truncated.avro");
+        std::fs::write(&path, file).unwrap();
+        assert!(read_dataset(path.to_str().unwrap()).is_err());
+    }
+}
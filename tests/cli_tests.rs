@@ -0,0 +1,257 @@
+// Tests for the command-line interface: subcommands, options and exit codes
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datastory(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_datastory"))
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_help_lists_subcommands() {
+        let output = datastory(&["--help"]);
+        assert_eq!(output.status.code(), Some(0));
+        let help = String::from_utf8_lossy(&output.stdout);
+        for command in ["report", "profile", "validate", "diff", "drift", "clean"] {
+            assert!(help.contains(command), "missing {}", command);
+        }
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(datastory(&["report", "--no-such-option", "x.csv"]).status.code(), Some(2));
+        assert_eq!(datastory(&["report", "does_not_exist.csv"]).status.code(), Some(3));
+
+        let dir = temp_dir("datastory_cli_validate");
+        let data = dir.join("data.csv");
+        std::fs::write(&data, "age\n34\n130\n").unwrap();
+        let rules = dir.join("rules.toml");
+        std::fs::write(&rules, "[[rules]]\ncolumn = \"age\"\nmax = 120\n").unwrap();
+        let output = datastory(&[
+            "validate",
+            data.to_str().unwrap(),
+            rules.to_str().unwrap(),
+            "--format",
+            "json",
+            "--output-dir",
+            dir.to_str().unwrap(),
+        ]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(dir.join("validation.json").exists());
+    }
+
+    #[test]
+    fn test_profile_with_input_options() {
+        let dir = temp_dir("datastory_cli_profile");
+        let data = dir.join("data.csv");
+        std::fs::write(&data, "a;b;c\n1;x;4\n2;y;5\n3;z;6\n").unwrap();
+        let output = datastory(&[
+            "profile",
+            data.to_str().unwrap(),
+            "-d",
+            ";",
+            "--exclude",
+            "b",
+            "--sample",
+            "2",
+            "-o",
+            dir.to_str().unwrap(),
+            "--quiet",
+        ]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(output.stdout.is_empty());
+        let profile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("profile.json")).unwrap())
+                .unwrap();
+        assert_eq!(profile["column_count"], 2);
+        assert_eq!(profile["row_count"], 2);
+    }
+
+    #[test]
+    fn test_file_argument_defaults_to_report() {
+        let dir = temp_dir("datastory_cli_legacy");
+        let data = dir.join("data.csv");
+        std::fs::write(&data, "a,b\n1,x\n2,y\n").unwrap();
+        let report = dir.join("out.md");
+        let output = datastory(&[
+            data.to_str().unwrap(),
+            report.to_str().unwrap(),
+            "--format",
+            "md",
+            "--no-charts",
+        ]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(report.exists());
+
+        // Global options may come before the data file
+        for global in [&["-v"][..], &["-q", "-j", "2"], &["--threads=2"], &["-vj2"]] {
+            std::fs::remove_file(&report).unwrap();
+            let mut args = global.to_vec();
+            args.extend([data.to_str().unwrap(), report.to_str().unwrap(), "-f", "md", "--no-charts"]);
+            let output = datastory(&args);
+            assert_eq!(output.status.code(), Some(0), "{:?}: {}", global, String::from_utf8_lossy(&output.stderr));
+            assert!(report.exists());
+        }
+    }
+
+    /// Run datastory in `dir` with `input` piped to its standard input
+    fn datastory_stdin(dir: &std::path::Path, args: &[&str], input: &[u8]) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_datastory"))
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    #[test]
+    fn test_stdin_to_stdout() {
+        let dir = temp_dir("datastory_cli_stdin");
+        let csv = b"a;b\n1;x\n2;y\n";
+        let output = datastory_stdin(&dir, &["-", "--format", "md", "--no-charts"], csv);
+        assert_eq!(output.status.code(), Some(0));
+        let markdown = String::from_utf8_lossy(&output.stdout);
+        assert!(markdown.starts_with("# "));
+        assert!(markdown.contains("Column `b`"));
+
+        let output = datastory_stdin(&dir, &["profile", "-"], csv);
+        let profile: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(profile["row_count"], 2);
+
+        // An explicit output name still writes a file, and HTML defaults to one
+        let output = datastory_stdin(&dir, &["profile", "-", "out.json"], csv);
+        assert!(output.stdout.starts_with(b"JSON profile generated"));
+        assert!(dir.join("out.json").exists());
+        datastory_stdin(&dir, &["report", "-", "--no-charts"], csv);
+        assert!(dir.join("rapport.html").exists());
+    }
+
+    #[test]
+    fn test_report_formats_come_from_the_renderers() {
+        let dir = temp_dir("datastory_cli_renderers");
+        let csv = b"a,b\n1,x\n2,y\n";
+        let output = datastory_stdin(&dir, &["report", "-", "--format", "markdown", "--no-charts"], csv);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("# "));
+
+        // The interactive renderer writes an HTML file, as `--interactive` does
+        let output = datastory_stdin(&dir, &["report", "-", "-f", "interactive"], csv);
+        assert!(output.stdout.starts_with(b"HTML report generated: rapport.html"));
+        let html = std::fs::read_to_string(dir.join("rapport.html")).unwrap();
+        assert!(html.contains("id='theme-toggle'"));
+
+        let output = datastory(&["report", "x.csv", "-f", "docx"]);
+        assert_eq!(output.status.code(), Some(2));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("html, interactive, md, json, pdf"));
+    }
+
+    #[test]
+    fn test_chart_options() {
+        let dir = temp_dir("datastory_cli_charts");
+        let data = dir.join("data.csv");
+        std::fs::write(&data, "n,c\n1,x\n2,y\n3,x\n").unwrap();
+        let report = dir.join("report.html");
+        let (data, report) = (data.to_str().unwrap(), report.to_str().unwrap());
+        let output = datastory(&["report", data, report, "--chart-format", "svg", "--chart-width", "320"]);
+        assert_eq!(output.status.code(), Some(0));
+        let svg = std::fs::read_to_string(dir.join("assets/hist_0.svg")).unwrap();
+        assert!(svg.starts_with("<svg width=\"320\" height=\"480\""));
+        assert!(std::fs::read_to_string(report).unwrap().contains("hist_0.svg"));
+
+        for bad in [["--chart-format", "gif"], ["--chart-dpi", "0"]] {
+            let output = datastory(&["report", data, report, bad[0], bad[1]]);
+            assert_eq!(output.status.code(), Some(2));
+        }
+
+        // Interactive charts are inline SVG of the chart size
+        let output = datastory(&["report", data, report, "--interactive", "--chart-width", "320", "--chart-height", "200"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(std::fs::read_to_string(report).unwrap().contains("<svg class='chart' width='320' height='200'"));
+        // ... so PNG, DPI and asset options are refused rather than ignored
+        for bad in [["--chart-format", "png"], ["--chart-dpi", "192"], ["--assets-dir", "img"]] {
+            let output = datastory(&["report", data, report, "--interactive", bad[0], bad[1]]);
+            assert_eq!(output.status.code(), Some(2));
+            assert!(String::from_utf8_lossy(&output.stderr).contains(bad[0]));
+        }
+    }
+
+    #[test]
+    fn test_threads_do_not_change_output() {
+        let dir = temp_dir("datastory_cli_threads");
+        let data = dir.join("wide.csv");
+        let header: Vec<String> = (0..60).map(|k| format!("c{}", k)).collect();
+        let mut csv = header.join(",") + "\n";
+        for row in 0..30 {
+            let cells: Vec<String> = (0..60)
+                .map(|k| match k % 3 {
+                    0 => ((row * (k + 1)) % 17).to_string(),
+                    1 => format!("{}.5", (row + k) % 11),
+                    _ => ["red", "green", "blue"][(row + k) % 3].to_string(),
+                })
+                .collect();
+            csv += &(cells.join(",") + "\n");
+        }
+        std::fs::write(&data, csv).unwrap();
+        let profile = |threads: &str| {
+            let output = datastory(&["profile", data.to_str().unwrap(), "-", "--threads", threads]);
+            assert_eq!(output.status.code(), Some(0));
+            let mut value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            value["generated_at"] = serde_json::Value::Null;
+            value
+        };
+        assert_eq!(profile("1"), profile("4"));
+        assert_eq!(datastory(&["profile", "x.csv", "--threads", "0"]).status.code(), Some(2));
+    }
+
+    #[test]
+    fn test_sample_is_stated_in_reports() {
+        let dir = temp_dir("datastory_cli_sample");
+        let data = dir.join("big.csv");
+        let mut csv = String::from("amount,region\n");
+        for row in 0..400 {
+            csv += &format!("{},{}\n", (row * 37) % 101, ["north", "south"][row % 2]);
+        }
+        std::fs::write(&data, csv).unwrap();
+        let data = data.to_str().unwrap();
+        let output = datastory(&["report", data, "-", "-f", "md", "--no-charts", "--sample", "50", "--sample-method", "reservoir"]);
+        assert_eq!(output.status.code(), Some(0));
+        let md = String::from_utf8(output.stdout).unwrap();
+        assert!(md.contains("**Sample:** Reservoir sample of 50 of 400 rows (seed 42)"));
+        assert!(md.contains(" ± "));
+
+        let output = datastory(&["profile", data, "-", "--sample-fraction", "0.25", "--stratify-by", "region", "--seed", "3"]);
+        assert_eq!(output.status.code(), Some(0));
+        let profile: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(profile["sample"]["method"], "stratified");
+        assert_eq!(profile["sample"]["rows"], 100);
+        assert_eq!(profile["row_count"], 100);
+        assert!(profile["columns"][0]["numeric"]["mean_margin"].as_f64().unwrap() > 0.0);
+
+        // The first rows are not a random sample: no error bars
+        let output = datastory(&["report", data, "-", "-f", "md", "--no-charts", "--sample", "50"]);
+        let md = String::from_utf8(output.stdout).unwrap();
+        assert!(md.contains("**Sample:** First 50 rows; the rest was not read"));
+        assert!(!md.contains(" ± "));
+
+        assert_eq!(datastory(&["report", data, "--sample-method", "fraction", "--sample", "5"]).status.code(), Some(2));
+        assert_eq!(datastory(&["report", data, "--sample-fraction", "2"]).status.code(), Some(2));
+    }
+}
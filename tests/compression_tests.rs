@@ -0,0 +1,85 @@
+// Unit tests for compressed input files
+use datastory::compression::Compression;
+use datastory::formats::read_dataset;
+use std::io::Write;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "name,age\nann,31\nbob,27\n";
+
+    fn write_temp(name: &str, bytes: &[u8]) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zstd(data: &[u8]) -> Vec<u8> {
+        zstd::encode_all(data, 0).unwrap()
+    }
+
+    fn bzip2(data: &[u8]) -> Vec<u8> {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn xz(data: &[u8]) -> Vec<u8> {
+        let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(Compression::from_magic(&gzip(b"x")), Some(Compression::Gzip));
+        assert_eq!(Compression::from_magic(&zstd(b"x")), Some(Compression::Zstd));
+        assert_eq!(Compression::from_magic(&bzip2(b"x")), Some(Compression::Bzip2));
+        assert_eq!(Compression::from_magic(&xz(b"x")), Some(Compression::Xz));
+        assert_eq!(Compression::from_magic(b"name,age"), None);
+        assert_eq!(Compression::from_extension("data.jsonl.BZ2"), Some(Compression::Bzip2));
+        assert_eq!(Compression::from_extension("data.csv"), None);
+    }
+
+    #[test]
+    fn test_read_compressed_csv() {
+        for (name, bytes) in [
+            ("datastory_compressed.csv.gz", gzip(CSV.as_bytes())),
+            ("datastory_compressed.csv.zst", zstd(CSV.as_bytes())),
+            ("datastory_compressed.csv.bz2", bzip2(CSV.as_bytes())),
+            ("datastory_compressed.csv.xz", xz(CSV.as_bytes())),
+            // Detected from the magic bytes alone
+            ("datastory_compressed_no_ext", gzip(CSV.as_bytes())),
+        ] {
+            let dataset = read_dataset(&write_temp(name, &bytes)).unwrap();
+            assert_eq!(dataset.headers, vec!["name", "age"], "{}", name);
+            assert_eq!(dataset.rows.len(), 2, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_read_compressed_json() {
+        let ndjson = b"{\"a\": 1}\n{\"a\": 2, \"b\": \"x\"}\n";
+        let dataset = read_dataset(&write_temp("datastory_compressed.jsonl.zst", &zstd(ndjson))).unwrap();
+        assert_eq!(dataset.headers, vec!["a", "b"]);
+        assert_eq!(dataset.rows[1], vec!["2", "x"]);
+
+        let json = b"[{\"a\": 1}, {\"a\": 2}]";
+        let dataset = read_dataset(&write_temp("datastory_compressed.json.gz", &gzip(json))).unwrap();
+        assert_eq!(dataset.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_truncated_archive_is_an_error() {
+        let mut bytes = gzip(CSV.as_bytes());
+        bytes.truncate(bytes.len() / 2);
+        assert!(read_dataset(&write_temp("datastory_truncated.csv.gz", &bytes)).is_err());
+    }
+}
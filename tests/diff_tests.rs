@@ -0,0 +1,95 @@
+// Unit tests for profile diffing
+use datastory::analyze::profile;
+use datastory::diff::{diff_datasets, diff_profiles, generate_diff_report};
+use datastory::formats::Dataset;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(headers: &[&str], rows: &[&[&str]]) -> Dataset {
+        Dataset {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|r| r.iter().map(|c| c.to_string()).collect())
+                .collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        }
+    }
+
+    #[test]
+    fn test_schema_changes() {
+        let old = dataset(&["id", "score", "legacy"], &[&["1", "10", "x"], &["2", "20", "y"]]);
+        let new = dataset(&["id", "score", "city"], &[&["1", "high", "Paris"], &["2", "low", "Lyon"]]);
+        let diff = diff_datasets(&old, &new, "old.csv", "new.csv");
+        assert_eq!(diff.added_columns.len(), 1);
+        assert_eq!(diff.added_columns[0].name, "city");
+        assert_eq!(diff.removed_columns[0].name, "legacy");
+        assert_eq!(diff.retyped_columns.len(), 1);
+        assert_eq!(diff.retyped_columns[0].name, "score");
+        assert_eq!(diff.retyped_columns[0].old_type, "numeric");
+        assert_eq!(diff.retyped_columns[0].new_type, "categorical");
+    }
+
+    #[test]
+    fn test_statistic_and_missing_rate_shifts() {
+        let old = dataset(&["v"], &[&["1"], &["2"], &["3"], &["4"]]);
+        let new = dataset(&["v"], &[&["2"], &["4"], &[""], &["6"], &["8"]]);
+        let diff = diff_datasets(&old, &new, "old.csv", "new.csv");
+        assert_eq!((diff.old_rows, diff.new_rows), (4, 5));
+        let col = &diff.columns[0];
+        assert_eq!(col.old_missing_rate, 0.0);
+        assert_eq!(col.new_missing_rate, 0.2);
+        let mean = col.stats.iter().find(|s| s.stat == "mean").unwrap();
+        assert_eq!((mean.old, mean.new, mean.delta), (2.5, 5.0, 2.5));
+        assert_eq!(mean.relative, Some(1.0));
+    }
+
+    #[test]
+    fn test_top_category_changes() {
+        let old = dataset(&["c"], &[&["a"], &["a"], &["b"]]);
+        let new = dataset(&["c"], &[&["c"], &["c"], &["a"]]);
+        let diff = diff_datasets(&old, &new, "old.csv", "new.csv");
+        let categories = diff.columns[0].categories.as_ref().unwrap();
+        assert_eq!(categories.new_top[0].value, "c");
+        assert_eq!(categories.entered, vec!["c".to_string()]);
+        assert_eq!(categories.left, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_diff_of_profiles() {
+        let old = dataset(&["flag", "n"], &[&["true", "1"], &["", "2"], &["false", ""]]);
+        let new = dataset(&["flag", "n"], &[&["true", "x"], &["true", "3"]]);
+        let diff = diff_profiles(&profile(&old).with_source("old.csv"), &profile(&new).with_source("new.csv"));
+        assert_eq!((diff.old_source.as_str(), diff.old_rows), ("old.csv", 3));
+        // Missing cells neither change the type nor count as categories
+        let flag = &diff.columns[0];
+        assert_eq!((flag.old_type.as_str(), flag.new_type.as_str()), ("boolean", "boolean"));
+        assert!((flag.old_missing_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(flag.categories.as_ref().unwrap().left, vec!["false".to_string()]);
+        let n = &diff.columns[1];
+        assert_eq!((n.old_type.as_str(), n.new_type.as_str()), ("numeric", "categorical"));
+        assert_eq!(diff.retyped_columns.len(), 1);
+        assert!(n.stats.is_empty() && n.categories.is_none());
+    }
+
+    #[test]
+    fn test_diff_report_formats() {
+        let old = dataset(&["a"], &[&["1"]]);
+        let new = dataset(&["a", "b"], &[&["2", "<x>"]]);
+        let diff = diff_datasets(&old, &new, "old.csv", "new.csv");
+        let dir = std::env::temp_dir();
+        for (format, ext) in [("html", "html"), ("md", "md"), ("json", "json")] {
+            let out = dir.join(format!("datastory_diff.{}", ext));
+            generate_diff_report(&diff, out.to_str().unwrap(), format).unwrap();
+            let text = std::fs::read_to_string(&out).unwrap();
+            assert!(text.contains("b"));
+        }
+        let html = std::fs::read_to_string(dir.join("datastory_diff.html")).unwrap();
+        assert!(html.contains("Added column 'b' (categorical)"));
+        assert!(generate_diff_report(&diff, "unused", "xml").is_err());
+    }
+}
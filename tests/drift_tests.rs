@@ -0,0 +1,109 @@
+// Unit tests for distribution drift metrics
+use datastory::drift::{
+    Severity, chi_square_test, drift_datasets, js_divergence, ks_test, psi_categorical,
+    psi_numeric, wasserstein,
+};
+use datastory::formats::Dataset;
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_identical_distributions_do_not_drift() {
+        let x: Vec<f64> = (0..100).map(|v| v as f64).collect();
+        assert!(psi_numeric(&x, &x).abs() < 1e-12);
+        let (d, p) = ks_test(&x, &x);
+        assert_eq!(d, 0.0);
+        assert!(p > 0.99);
+        assert_eq!(wasserstein(&x, &x), 0.0);
+        let c = strings(&["a", "b", "b"]);
+        assert!(psi_categorical(&c, &c).abs() < 1e-12);
+        assert!(js_divergence(&c, &c).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_numeric_metrics() {
+        assert!((wasserstein(&[0.0, 1.0, 3.0], &[5.0, 6.0, 8.0]) - 5.0).abs() < 1e-12);
+        let (d, p) = ks_test(&[1.0, 2.0, 3.0, 4.0], &[3.0, 4.0, 5.0, 6.0]);
+        assert!((d - 0.5).abs() < 1e-12);
+        assert!(p > 0.05 && p < 1.0);
+        let reference: Vec<f64> = (0..1000).map(|v| v as f64).collect();
+        let shifted: Vec<f64> = reference.iter().map(|v| v + 500.0).collect();
+        assert!(psi_numeric(&reference, &shifted) > 0.25);
+    }
+
+    #[test]
+    fn test_categorical_metrics() {
+        let reference = strings(&[&["a"; 10][..], &["b"; 10][..]].concat());
+        let current = strings(&[&["a"; 15][..], &["b"; 5][..]].concat());
+        let (chi2, p) = chi_square_test(&reference, &current);
+        assert!((chi2 - 8.0 / 3.0).abs() < 1e-9);
+        assert!((p - 0.1025).abs() < 1e-3);
+        assert!((js_divergence(&strings(&["a"]), &strings(&["b"])) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(Severity::from_psi(0.05), Severity::None);
+        assert_eq!(Severity::from_psi(0.2), Severity::Moderate);
+        assert_eq!(Severity::from_psi(0.3), Severity::High);
+        let reference = Dataset {
+            headers: strings(&["v", "only_old"]),
+            rows: (0..50).map(|i| vec![i.to_string(), "x".to_string()]).collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let current = Dataset {
+            headers: strings(&["v"]),
+            rows: (0..50).map(|i| vec![format!("v{}", i)]).collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let report = drift_datasets(&reference, &current, "ref.csv", "cur.csv");
+        assert_eq!(report.unmatched_columns, vec!["only_old".to_string()]);
+        // numeric -> categorical is always a high severity change
+        assert_eq!(report.columns[0].severity, Severity::High);
+        assert_eq!(report.max_severity(), Severity::High);
+    }
+
+    fn arrow_dataset(zips: Vec<&str>, amounts: Vec<Option<f64>>) -> Dataset {
+        use arrow_array::{ArrayRef, Float64Array, RecordBatch, RecordBatchIterator, StringArray};
+        use arrow_schema::{DataType, Field, Schema};
+        let schema = Schema::new(vec![
+            Field::new("zip", DataType::Utf8, false),
+            Field::new("amount", DataType::Float64, true),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(zips)),
+            Arc::new(Float64Array::from(amounts)),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), columns).unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch.clone())], batch.schema());
+        datastory::formats::arrow_dataset(reader).unwrap()
+    }
+
+    #[test]
+    fn test_schema_types_pick_the_test() {
+        let reference = arrow_dataset(vec!["01234", "01234", "02345"], vec![Some(1.0); 3]);
+        let current = arrow_dataset(vec!["01234", "02345", "02345"], vec![None; 3]);
+        let report = drift_datasets(&reference, &current, "ref.arrow", "cur.arrow");
+        // Zip codes are text, compared as categories even though they look numeric
+        let zip = &report.columns[0];
+        assert_eq!(zip.current_type, "categorical");
+        assert!(zip.chi_square.is_some());
+        assert!(zip.ks_statistic.is_none());
+        // A numeric column holding only nulls is still numeric, not a type change
+        let amount = &report.columns[1];
+        assert_eq!(amount.reference_type, "numeric");
+        assert_eq!(amount.current_type, "numeric");
+        assert_eq!(amount.severity, Severity::None);
+    }
+}
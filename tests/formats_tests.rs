@@ -0,0 +1,195 @@
+// Unit tests for file format readers
+use datastory::formats::{
+    CsvOptions, Dataset, ReadOptions, parse_encoding, read_dataset, read_dataset_with_options,
+};
+use datastory::sniff::InputFormat;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_read_csv_dataset_keeps_headers() {
+        let path = write_temp("datastory_headers.csv", "name,age\nann,31\nbob,27\n");
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["name", "age"]);
+        assert_eq!(dataset.rows.len(), 2);
+        assert_eq!(dataset.column("age").unwrap(), vec!["31", "27"]);
+    }
+
+    #[test]
+    fn test_read_json_dataset_aligns_keys() {
+        let path = write_temp(
+            "datastory_records.json",
+            r#"[{"name": "ann", "age": 31}, {"age": 27, "city": "Paris"}, {"name": null}]"#,
+        );
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["name", "age", "city"]);
+        assert_eq!(dataset.rows[0], vec!["ann", "31", ""]);
+        assert_eq!(dataset.rows[1], vec!["", "27", "Paris"]);
+        assert_eq!(dataset.rows[2], vec!["", "", ""]);
+    }
+
+    #[test]
+    fn test_split_columns() {
+        let dataset = Dataset {
+            headers: vec!["a".to_string(), "b".to_string()],
+            rows: vec![
+                vec!["1".to_string(), "x".to_string()],
+                vec!["n/a".to_string(), "2".to_string()],
+            ],
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let (num, cat) = dataset.split_columns();
+        assert_eq!(num, vec![vec![1.0], vec![2.0]]);
+        assert_eq!(cat, vec![vec!["n/a".to_string()], vec!["x".to_string()]]);
+    }
+
+    #[test]
+    fn test_read_csv_with_delimiter() {
+        let path = write_temp("datastory_semicolon.csv", "name;age\nann;31\n");
+        let options = ReadOptions {
+            csv: CsvOptions {
+                delimiter: Some(b';'),
+                ..CsvOptions::default()
+            },
+            ..ReadOptions::default()
+        };
+        let dataset = read_dataset_with_options(&path, &options).unwrap();
+        assert_eq!(dataset.headers, vec!["name", "age"]);
+        assert_eq!(dataset.rows[0], vec!["ann", "31"]);
+    }
+
+    #[test]
+    fn test_select_columns() {
+        let dataset = Dataset {
+            headers: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            rows: vec![vec!["1".to_string(), "2".to_string(), "3".to_string()]],
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let only = dataset.select_columns(&["c".to_string(), "a".to_string()], &[]).unwrap();
+        assert_eq!(only.headers, vec!["a", "c"]);
+        assert_eq!(only.rows[0], vec!["1", "3"]);
+        let without = dataset.select_columns(&[], &["b".to_string()]).unwrap();
+        assert_eq!(without.headers, vec!["a", "c"]);
+        assert!(dataset.select_columns(&["missing".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_format_detected_from_content() {
+        let path = write_temp("datastory_latin1", "");
+        std::fs::write(&path, b"nom;ville\nRen\xe9;Z\xfcrich\n").unwrap();
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["nom", "ville"]);
+        assert_eq!(dataset.rows[0], vec!["Ren\u{e9}", "Z\u{fc}rich"]);
+
+        let path = write_temp("datastory_records.txt", "{\"a\": 1}\n\n{\"b\": \"x\"}\n");
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["a", "b"]);
+        assert_eq!(dataset.rows, vec![vec!["1", ""], vec!["", "x"]]);
+
+        let path = write_temp("datastory_no_header.CSV", "1,2\n3,4\n");
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["column_1", "column_2"]);
+        assert_eq!(dataset.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_input_format_override() {
+        let path = write_temp("datastory_override.data", "{\"a\": 1}\n");
+        let options = ReadOptions {
+            format: Some(InputFormat::Csv),
+            ..ReadOptions::default()
+        };
+        let dataset = read_dataset_with_options(&path, &options).unwrap();
+        assert_eq!(dataset.headers, vec!["{\"a\": 1}"]);
+    }
+
+    #[test]
+    fn test_csv_options_partner_file() {
+        let path = write_temp("datastory_partner.csv", "");
+        std::fs::write(
+            &path,
+            b"Partner export\n# generated nightly\nnom;ville;age\nRen\xe9;Z\xfcrich;31\n# skipped\nAnn;Paris\n",
+        )
+        .unwrap();
+        let options = ReadOptions {
+            csv: CsvOptions {
+                skip_rows: 1,
+                comment: Some(b'#'),
+                flexible: true,
+                encoding: Some(parse_encoding("latin1").unwrap()),
+                ..CsvOptions::default()
+            },
+            ..ReadOptions::default()
+        };
+        let dataset = read_dataset_with_options(&path, &options).unwrap();
+        assert_eq!(dataset.headers, vec!["nom", "ville", "age"]);
+        assert_eq!(dataset.rows[0], vec!["Ren\u{e9}", "Z\u{fc}rich", "31"]);
+        assert_eq!(dataset.rows[1], vec!["Ann", "Paris", ""]);
+
+        let strict = ReadOptions {
+            csv: CsvOptions {
+                flexible: false,
+                ..options.csv.clone()
+            },
+            ..ReadOptions::default()
+        };
+        assert!(read_dataset_with_options(&path, &strict).is_err());
+    }
+
+    #[test]
+    fn test_csv_options_quoting_and_header() {
+        let path = write_temp("datastory_escape.csv", "1,'it\\'s'\n2,'plain'\n");
+        let options = ReadOptions {
+            csv: CsvOptions {
+                quote: Some(b'\''),
+                escape: Some(b'\\'),
+                has_headers: Some(false),
+                ..CsvOptions::default()
+            },
+            ..ReadOptions::default()
+        };
+        let dataset = read_dataset_with_options(&path, &options).unwrap();
+        assert_eq!(dataset.headers, vec!["column_1", "column_2"]);
+        assert_eq!(dataset.rows[0], vec!["1", "it's"]);
+        assert!(parse_encoding("no-such-encoding").is_err());
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        use datastory::error::DataError;
+        let path = write_temp("datastory_bad.ndjson", "{\"a\": 1}\n{\"a\": \n");
+        let err = read_dataset(&path).unwrap_err();
+        assert!(matches!(err.downcast_ref::<DataError>(), Some(DataError::Parse(_))));
+
+        let err = read_dataset("/no/such/file.csv").unwrap_err();
+        let err = err.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        let dataset = read_dataset(&write_temp("datastory_kinds.csv", "a\n1\n")).unwrap();
+        let output = std::env::temp_dir().join("datastory_kinds.txt");
+        let err = datastory::report::generate_report(
+            &dataset,
+            "kinds",
+            output.to_str().unwrap(),
+            "txt",
+            &datastory::report::HtmlOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DataError>(),
+            Some(DataError::UnsupportedFormat(_))
+        ));
+    }
+}
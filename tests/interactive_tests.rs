@@ -0,0 +1,76 @@
+// Unit tests for the interactive HTML report
+use datastory::interactive::generate_interactive_html_report;
+use datastory::profile::build_profile;
+use datastory::report::HtmlOptions;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interactive_report_is_self_contained() {
+        let report = std::env::temp_dir().join("datastory_interactive.html");
+        let num_columns = [vec![1.0, 2.0, 3.0, 100.0], vec![2.0, 4.0, 6.0, 8.0]];
+        let cat_columns = [vec!["a".to_string(), "b".to_string(), "a".to_string()]];
+        let headers = ["num_col".to_string(), "other".to_string()];
+        let profile = build_profile(&num_columns, &cat_columns, &headers, "test.csv");
+        generate_interactive_html_report(&profile, &num_columns, &cat_columns, report.to_str().unwrap(), &HtmlOptions::default())
+            .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.contains("id='overview'"));
+        assert!(html.contains("class='sortable'"));
+        assert!(html.contains("<details class='column'"));
+        assert!(html.contains("data-tip="));
+        assert!(html.contains("id='theme-toggle'"));
+        // No CDN or external assets
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("<link"));
+    }
+
+    #[test]
+    fn test_interactive_report_escapes_user_content() {
+        let report = std::env::temp_dir().join("datastory_interactive_escaping.html");
+        let payload = "</script><script>alert(1)</script>".to_string();
+        let num_columns = [vec![], vec![]];
+        let cat_columns = [vec![payload.clone(), "x' onmouseover='y".to_string()], vec![]];
+        let headers = [payload.clone(), "other".to_string()];
+        let profile = build_profile(&num_columns, &cat_columns, &headers, "<svg onload=alert(1)>");
+        generate_interactive_html_report(&profile, &num_columns, &cat_columns, report.to_str().unwrap(), &HtmlOptions::default())
+            .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        // Only the report's own inline script may appear
+        assert_eq!(html.matches("<script>").count(), 1);
+        assert!(!html.contains("<svg onload"));
+        assert!(!html.contains("x' onmouseover"));
+        assert!(html.contains("&lt;/script&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_interactive_report_options() {
+        let dir = std::env::temp_dir();
+        let report = dir.join("datastory_interactive_options.html");
+        let num_columns = [vec![1.0, 2.0, 3.0]];
+        let cat_columns = [vec![]];
+        let headers = ["n".to_string()];
+        let profile = build_profile(&num_columns, &cat_columns, &headers, "test.csv");
+        let render = |options: &HtmlOptions| {
+            generate_interactive_html_report(&profile, &num_columns, &cat_columns, report.to_str().unwrap(), options)
+                .unwrap();
+            std::fs::read_to_string(&report).unwrap()
+        };
+
+        let mut options = HtmlOptions::default();
+        options.chart.width = 300;
+        options.chart.height = 150;
+        assert!(render(&options).contains("<svg class='chart' width='300' height='150' viewBox='0 0 300 150'"));
+
+        options.charts = false;
+        let html = render(&options);
+        assert!(!html.contains("<svg") && html.contains("id='overview'"));
+
+        let template = dir.join("datastory_interactive_template.html");
+        std::fs::write(&template, "<h1>{{ file }}</h1>{% for c in columns %}{{ c.name }}{% endfor %}").unwrap();
+        options.template = Some(template.to_str().unwrap().to_string());
+        assert_eq!(render(&options), "<h1>test.csv</h1>n");
+    }
+}
@@ -0,0 +1,73 @@
+// Unit tests for the JSON profile output
+use datastory::profile::{
+    PROFILE_SCHEMA, PROFILE_SCHEMA_VERSION, build_profile, generate_json_profile, read_json_profile,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<Vec<f64>>, Vec<Vec<String>>, Vec<String>) {
+        let num_columns = vec![vec![1.0, 2.0, 3.0, 4.0], vec![]];
+        let cat_columns = vec![
+            vec![],
+            ["b", "a", "b", "c"].iter().map(|s| s.to_string()).collect(),
+        ];
+        let headers = vec!["age".to_string(), "group".to_string()];
+        (num_columns, cat_columns, headers)
+    }
+
+    #[test]
+    fn test_profile_contents() {
+        let (num, cat, headers) = sample();
+        let profile = build_profile(&num, &cat, &headers, "data.csv");
+        assert_eq!(profile.schema_version, PROFILE_SCHEMA_VERSION);
+        assert_eq!(profile.row_count, 4);
+        assert_eq!(profile.column_count, 2);
+        let numeric = profile.columns[0].numeric.as_ref().unwrap();
+        assert_eq!(numeric.mean, 2.5);
+        let categorical = profile.columns[1].categorical.as_ref().unwrap();
+        assert_eq!(categorical.mode, "b");
+        let freq: Vec<_> = categorical
+            .frequencies
+            .iter()
+            .map(|f| (f.value.as_str(), f.count))
+            .collect();
+        assert_eq!(freq, vec![("b", 2), ("a", 1), ("c", 1)]);
+    }
+
+    #[test]
+    fn test_json_output_has_schema_required_fields() {
+        let (num, cat, headers) = sample();
+        let out = std::env::temp_dir().join("datastory_profile.json");
+        generate_json_profile(&num, &cat, &headers, "data.csv", out.to_str().unwrap()).unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(PROFILE_SCHEMA).unwrap();
+        for key in schema["required"].as_array().unwrap() {
+            assert!(doc.get(key.as_str().unwrap()).is_some(), "missing {}", key);
+        }
+        let column_schema = &schema["properties"]["columns"]["items"];
+        for column in doc["columns"].as_array().unwrap() {
+            for key in column_schema["required"].as_array().unwrap() {
+                assert!(column.get(key.as_str().unwrap()).is_some(), "missing {}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_profile_round_trip() {
+        let (num, cat, headers) = sample();
+        let out = std::env::temp_dir().join("datastory_profile_round_trip.json");
+        let out = out.to_str().unwrap();
+        generate_json_profile(&num, &cat, &headers, "data.csv", out).unwrap();
+        let profile = read_json_profile(out).unwrap();
+        assert_eq!(profile.source, "data.csv");
+        assert_eq!(profile.columns.len(), 2);
+        assert_eq!(profile.columns[0].numeric.as_ref().unwrap().unique, 4);
+        assert_eq!(profile.columns[1].categorical.as_ref().unwrap().mode, "b");
+
+        std::fs::write(out, "{\"schema_version\": 99}").unwrap();
+        assert!(read_json_profile(out).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+// Unit tests for pluggable report renderers
+use datastory::error::DataError;
+use datastory::formats::Dataset;
+use datastory::renderer::{
+    self, HtmlRenderer, JsonRenderer, RendererRegistry, ReportInput, ReportRenderer,
+};
+use datastory::report::{HtmlOptions, generate_report, write_report};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A LaTeX table of the numeric columns, as a downstream crate could add
+    struct LatexRenderer;
+
+    impl ReportRenderer for LatexRenderer {
+        fn name(&self) -> &str {
+            "latex"
+        }
+
+        fn aliases(&self) -> &[&str] {
+            &["tex"]
+        }
+
+        fn extension(&self) -> &str {
+            "tex"
+        }
+
+        fn description(&self) -> &str {
+            "LaTeX report"
+        }
+
+        fn render(&self, input: &ReportInput, report_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+            let mut tex = String::from("\\begin{tabular}{lr}\n");
+            for column in &input.profile.columns {
+                if let Some(numeric) = &column.numeric {
+                    tex.push_str(&format!("{} & {:.2} \\\\\n", column.name, numeric.mean));
+                }
+            }
+            tex.push_str("\\end{tabular}\n");
+            write_report(report_name, &tex)
+        }
+    }
+
+    fn dataset() -> Dataset {
+        Dataset {
+            headers: vec!["amount".to_string(), "city".to_string()],
+            rows: vec![
+                vec!["1".to_string(), "Paris".to_string()],
+                vec!["2".to_string(), "Lyon".to_string()],
+            ],
+            types: None,
+            numbers: None,
+            sample: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = RendererRegistry::default();
+        assert_eq!(registry.names(), vec!["html", "interactive", "md", "json", "pdf"]);
+        assert_eq!(registry.get("markdown").unwrap().name(), "md");
+        assert_eq!(registry.get("interactive").unwrap().extension(), "html");
+        assert!(registry.get("latex").is_none());
+
+        let mut registry = RendererRegistry::new();
+        assert!(registry.names().is_empty());
+        registry.register(JsonRenderer);
+        registry.register(HtmlRenderer);
+        // Registering a name again replaces the renderer
+        registry.register(JsonRenderer);
+        assert_eq!(registry.names(), vec!["html", "json"]);
+    }
+
+    #[test]
+    fn test_registered_renderer_is_used_by_generate_report() {
+        renderer::register(LatexRenderer);
+        assert!(renderer::renderer_names().contains(&"latex".to_string()));
+        let out = std::env::temp_dir().join("datastory_report.tex");
+        let options = HtmlOptions::default();
+        generate_report(&dataset(), "data.csv", out.to_str().unwrap(), "tex", &options).unwrap();
+        let tex = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(tex, "\\begin{tabular}{lr}\namount & 1.50 \\\\\n\\end{tabular}\n");
+    }
+
+    #[test]
+    fn test_unknown_format_lists_renderers() {
+        let options = HtmlOptions::default();
+        let err = generate_report(&dataset(), "data.csv", "out.x", "docx", &options).unwrap_err();
+        match err.downcast_ref::<DataError>() {
+            Some(DataError::UnsupportedFormat(message)) => {
+                assert!(message.contains("'docx'"));
+                assert!(message.contains("html, interactive, md, json, pdf"));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
@@ -1,16 +1,283 @@
 // Unit tests for report generation (mocked data)
-use datastory::report::generate_html_report;
+use datastory::formats::Dataset;
+use datastory::profile::build_profile;
+use datastory::report::{
+    ChartFormat, ChartOptions, HtmlOptions, generate_html_report, generate_html_report_with_options,
+    generate_markdown_report, generate_report, render_html_report, save_bar_chart, svg_histogram,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::useless_vec)]
     fn test_generate_html_report_basic() {
+        let dir = std::env::temp_dir().join("datastory_basic_report");
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.html");
         let num_columns = vec![vec![1.0, 2.0, 3.0]];
         let cat_columns = vec![vec!["a".to_string(), "b".to_string()]];
         let headers = vec!["num_col".to_string(), "cat_col".to_string()];
-        let result = generate_html_report(&num_columns[..], &cat_columns[..], &headers[..], "test.csv", "report.html");
+        let result = generate_html_report(&num_columns[..], &cat_columns[..], &headers[..], "test.csv", report.to_str().unwrap());
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_self_contained_report_inlines_charts() {
+        let dir = std::env::temp_dir().join("datastory_self_contained");
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.html");
+        let options = HtmlOptions {
+            self_contained: true,
+            ..HtmlOptions::default()
+        };
+        generate_html_report_with_options(
+            &[vec![1.0, 2.0, 3.0]],
+            &[vec!["a".to_string(), "b".to_string()]],
+            &["num_col".to_string(), "cat_col".to_string()],
+            "test.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.contains("<svg"));
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("<img"));
+        assert!(!dir.join("assets").exists());
+    }
+
+    #[test]
+    fn test_assets_dir_is_relative_to_report() {
+        let dir = std::env::temp_dir().join("datastory_assets_dir");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.html");
+        let options = HtmlOptions {
+            assets_dir: "charts".to_string(),
+            ..HtmlOptions::default()
+        };
+        generate_html_report_with_options(
+            &[vec![1.0, 2.0, 3.0]],
+            &[],
+            &["num_col".to_string()],
+            "test.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert!(dir.join("charts/hist_0.png").exists());
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.replace("&#x2f;", "/").contains("src='charts/hist_0.png'"));
+    }
+
+    #[test]
+    fn test_user_content_is_escaped() {
+        let report = std::env::temp_dir().join("datastory_escaping.html");
+        let payload = "<script>alert('x')</script>".to_string();
+        let options = HtmlOptions {
+            self_contained: true,
+            ..HtmlOptions::default()
+        };
+        generate_html_report_with_options(
+            &[vec![1.0, 2.0, 3.0], vec![]],
+            &[vec![], vec![payload.clone(), payload.clone(), "\"'&".to_string()]],
+            &[payload.clone(), "<b onmouseover=x>".to_string()],
+            "<img src=x onerror=alert(1)>.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img src=x"));
+        assert!(!html.contains("<b onmouseover"));
+        assert!(html.contains("&lt;script&gt;alert("));
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;.csv"));
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let dir = std::env::temp_dir().join("datastory_markdown");
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.md");
+        generate_markdown_report(
+            &[vec![1.0, 2.0, 3.0]],
+            &[vec![], vec!["a".to_string(), "b".to_string(), "a".to_string()]],
+            &["num_col".to_string(), "cat_col".to_string()],
+            "test.csv",
+            report.to_str().unwrap(),
+            &HtmlOptions::default(),
+        )
+        .unwrap();
+        let md = std::fs::read_to_string(&report).unwrap();
+        assert!(md.starts_with("# Data Storytelling Report"));
+        assert!(md.contains("### Column `num_col`"));
+        assert!(md.contains("- Mode: 'a' (2 occurrences)"));
+        assert!(md.contains("![Histogram num_col](assets/hist_0.png)"));
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let dir = std::env::temp_dir().join("datastory_custom_template");
+        std::fs::create_dir_all(&dir).unwrap();
+        let template = dir.join("brand.html");
+        std::fs::write(
+            &template,
+            "<h1>ACME profile of {{ file }}</h1>{% for col in columns|reverse %}<p>{{ col.name }}</p>{% endfor %}",
+        )
+        .unwrap();
+        let report = dir.join("report.html");
+        let options = HtmlOptions {
+            template: Some(template.to_string_lossy().into_owned()),
+            ..HtmlOptions::default()
+        };
+        generate_html_report_with_options(
+            &[vec![1.0, 2.0], vec![3.0, 4.0]],
+            &[],
+            &["first".to_string(), "<second>".to_string()],
+            "data.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert_eq!(
+            html,
+            "<h1>ACME profile of data.csv</h1><p>&lt;second&gt;</p><p>first</p>"
+        );
+    }
+
+    #[test]
+    fn test_charts_can_be_disabled() {
+        let dir = std::env::temp_dir().join("datastory_no_charts");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.html");
+        let options = HtmlOptions {
+            charts: false,
+            ..HtmlOptions::default()
+        };
+        generate_html_report_with_options(
+            &[vec![1.0, 2.0, 3.0]],
+            &[],
+            &["num_col".to_string()],
+            "test.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let html = std::fs::read_to_string(&report).unwrap();
+        assert!(html.contains("num_col"));
+        assert!(!html.contains("<img"));
+        assert!(!dir.join("assets").exists());
+    }
+
+    #[test]
+    fn test_render_self_contained_html_to_string() {
+        let options = HtmlOptions {
+            self_contained: true,
+            ..HtmlOptions::default()
+        };
+        let num_columns = [vec![1.0, 2.0, 3.0]];
+        let profile = build_profile(&num_columns, &[], &["num_col".to_string()], "DataFrame");
+        let html = render_html_report(&profile, &num_columns, &[], "", &options).unwrap();
+        assert!(html.contains("DataFrame"));
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("<img"));
+    }
+
+    #[test]
+    fn test_generate_report_formats() {
+        let dir = std::env::temp_dir().join("datastory_generate_report");
+        std::fs::create_dir_all(&dir).unwrap();
+        let dataset = Dataset {
+            headers: vec!["n".to_string(), "c".to_string()],
+            rows: vec![vec!["1".to_string(), "x".to_string()], vec!["2".to_string(), "y".to_string()]],
+            types: None,
+            numbers: None,
+            sample: None,
+        };
+        let options = HtmlOptions {
+            charts: false,
+            ..HtmlOptions::default()
+        };
+        let json = dir.join("profile.json");
+        generate_report(&dataset, "data.csv", json.to_str().unwrap(), "json", &options).unwrap();
+        let profile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(profile["row_count"], 2);
+        let md = dir.join("report.md");
+        generate_report(&dataset, "data.csv", md.to_str().unwrap(), "md", &options).unwrap();
+        assert!(std::fs::read_to_string(&md).unwrap().contains("Column `c`"));
+        assert!(generate_report(&dataset, "data.csv", "x.docx", "docx", &options).is_err());
+    }
+
+    #[test]
+    fn test_svg_chart_files() {
+        let dir = std::env::temp_dir().join("datastory_svg_charts");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = dir.join("report.md");
+        let options = HtmlOptions {
+            chart: ChartOptions {
+                format: ChartFormat::Svg,
+                width: 800,
+                height: 300,
+                ..ChartOptions::default()
+            },
+            ..HtmlOptions::default()
+        };
+        generate_markdown_report(
+            &[vec![1.0, 2.0, 3.0], vec![]],
+            &[vec![], vec!["a".to_string(), "b".to_string()]],
+            &["num_col".to_string(), "cat_col".to_string()],
+            "test.csv",
+            report.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let svg = std::fs::read_to_string(dir.join("assets/hist_0.svg")).unwrap();
+        assert!(svg.starts_with("<svg width=\"800\" height=\"300\""));
+        assert!(dir.join("assets/bar_1.svg").exists());
+        assert!(!dir.join("assets/hist_0.png").exists());
+        let markdown = std::fs::read_to_string(&report).unwrap();
+        assert!(markdown.contains("(assets/boxplot_0.svg)"));
+    }
+
+    #[test]
+    fn test_png_chart_size_and_dpi() {
+        let png_size = |path: &std::path::Path| {
+            let bytes = std::fs::read(path).unwrap();
+            let read = |at: usize| u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap());
+            (read(16), read(20))
+        };
+        let dir = std::env::temp_dir().join("datastory_png_dpi");
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = ["a".to_string(), "b".to_string(), "a".to_string()];
+        let file = dir.join("bar.png");
+        let options = ChartOptions {
+            width: 400,
+            height: 200,
+            dpi: 192,
+            ..ChartOptions::default()
+        };
+        assert_eq!(options.pixel_size(), (800, 400));
+        save_bar_chart(file.to_str().unwrap(), &data, "c", &options).unwrap();
+        assert_eq!(png_size(&file), (800, 400));
+        save_bar_chart(file.to_str().unwrap(), &data, "c", &ChartOptions::default()).unwrap();
+        assert_eq!(png_size(&file), (640, 480));
+
+        // Inline SVG keeps the layout size whatever the DPI
+        let svg = svg_histogram(&[1.0, 2.0], "n", &options).unwrap();
+        assert!(svg.starts_with("<svg width=\"400\" height=\"200\""));
+        let empty = ChartOptions {
+            width: 0,
+            ..ChartOptions::default()
+        };
+        assert!(svg_histogram(&[1.0, 2.0], "n", &empty).is_err());
+        assert_eq!("SVG".parse::<ChartFormat>().unwrap(), ChartFormat::Svg);
+        assert!("gif".parse::<ChartFormat>().is_err());
+    }
 }
@@ -0,0 +1,160 @@
+// Unit tests for row sampling
+use datastory::formats::{read_dataset_with_options, Dataset, ReadOptions};
+use datastory::sample::{mean_margin, SampleMethod, Sampling};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(n: usize) -> Dataset {
+        Dataset {
+            headers: vec!["i".to_string(), "group".to_string()],
+            rows: (0..n)
+                .map(|i| vec![i.to_string(), if i % 4 == 0 { "a" } else { "b" }.to_string()])
+                .collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        }
+    }
+
+    fn sampling(method: Option<SampleMethod>, rows: Option<usize>, fraction: Option<f64>) -> Sampling {
+        Sampling::new(method, rows, fraction, None, Some(7)).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_head_keeps_first_rows() {
+        let sample = Sampling::head(3).apply(numbers(10)).unwrap();
+        assert_eq!(sample.rows.iter().map(|r| r[0].as_str()).collect::<Vec<_>>(), ["0", "1", "2"]);
+        let info = sample.sample.unwrap();
+        assert_eq!((info.rows, info.population), (3, Some(10)));
+        assert!(!info.is_random());
+        assert_eq!(info.description(), "First 3 of 10 rows");
+        assert_eq!(info.mean_margin(&[1.0, 2.0, 3.0]), None);
+    }
+
+    #[test]
+    fn test_reservoir_is_seeded_and_ordered() {
+        let reservoir = sampling(Some(SampleMethod::Reservoir), Some(20), None);
+        let first = reservoir.apply(numbers(1000)).unwrap();
+        let again = reservoir.apply(numbers(1000)).unwrap();
+        assert_eq!(first.rows, again.rows);
+        assert_eq!(first.rows.len(), 20);
+        let positions: Vec<usize> = first.rows.iter().map(|r| r[0].parse().unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        // Not just the head of the file
+        assert!(*positions.last().unwrap() > 100);
+        let info = first.sample.unwrap();
+        assert_eq!(info.description(), "Reservoir sample of 20 of 1000 rows (seed 7)");
+        let other = Sampling::new(Some(SampleMethod::Reservoir), Some(20), None, None, Some(8))
+            .unwrap()
+            .unwrap();
+        assert_ne!(other.apply(numbers(1000)).unwrap().rows, first.rows);
+    }
+
+    #[test]
+    fn test_fraction_keeps_about_the_share() {
+        let sample = sampling(None, None, Some(0.1)).apply(numbers(5000)).unwrap();
+        let info = sample.sample.unwrap();
+        assert_eq!(info.method, SampleMethod::Fraction);
+        assert!((400..600).contains(&info.rows), "{} rows", info.rows);
+        assert_eq!(info.fraction, Some(0.1));
+    }
+
+    #[test]
+    fn test_stratified_keeps_proportions() {
+        let stratified = Sampling::new(None, Some(40), None, Some("group".to_string()), None)
+            .unwrap()
+            .unwrap();
+        let sample = stratified.apply(numbers(1000)).unwrap();
+        assert_eq!(sample.rows.len(), 40);
+        assert_eq!(sample.rows.iter().filter(|r| r[1] == "a").count(), 10);
+        let info = sample.sample.unwrap();
+        assert_eq!(info.method, SampleMethod::Stratified);
+        assert_eq!(info.seed, Some(42));
+
+        // A fraction is taken from every stratum
+        let by_fraction = Sampling::new(None, None, Some(0.5), Some("group".to_string()), None)
+            .unwrap()
+            .unwrap();
+        let sample = by_fraction.apply(numbers(100)).unwrap();
+        assert_eq!(sample.rows.iter().filter(|r| r[1] == "a").count(), 13);
+        assert_eq!(sample.rows.iter().filter(|r| r[1] == "b").count(), 38);
+
+        let unknown = Sampling::new(None, Some(5), None, Some("nope".to_string()), None)
+            .unwrap()
+            .unwrap();
+        assert!(unknown.apply(numbers(10)).is_err());
+    }
+
+    #[test]
+    fn test_sample_of_every_row_is_no_sample() {
+        let sample = sampling(Some(SampleMethod::Reservoir), Some(50), None).apply(numbers(10)).unwrap();
+        assert_eq!(sample.rows.len(), 10);
+        assert!(sample.sample.is_none());
+    }
+
+    #[test]
+    fn test_invalid_sampling() {
+        assert!(Sampling::new(None, None, None, None, None).unwrap().is_none());
+        assert!(Sampling::new(None, Some(5), Some(0.5), None, None).is_err());
+        assert!(Sampling::new(None, None, Some(1.5), None, None).is_err());
+        assert!(Sampling::new(None, None, Some(0.0), None, None).is_err());
+        assert!(Sampling::new(Some(SampleMethod::Reservoir), None, Some(0.5), None, None).is_err());
+        assert!(Sampling::new(Some(SampleMethod::Fraction), Some(5), None, None, None).is_err());
+        assert!(Sampling::new(Some(SampleMethod::Stratified), Some(5), None, None, None).is_err());
+        assert!(Sampling::new(Some(SampleMethod::Head), Some(5), None, Some("g".to_string()), None).is_err());
+        assert!(Sampling::new(None, None, None, None, Some(3)).is_err());
+        assert_eq!("random".parse::<SampleMethod>().unwrap(), SampleMethod::Fraction);
+        assert!("bogus".parse::<SampleMethod>().is_err());
+    }
+
+    #[test]
+    fn test_mean_margin() {
+        let values: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        let wide = mean_margin(&values, 1_000_000).unwrap();
+        // 1.96 * sd / sqrt(n), sd of 0..100 is about 29.01
+        assert!((wide - 5.686).abs() < 0.01, "{}", wide);
+        let narrow = mean_margin(&values, 200).unwrap();
+        assert!(narrow < wide);
+        assert_eq!(mean_margin(&values, 100), Some(0.0));
+        assert_eq!(mean_margin(&[1.0], 10), None);
+    }
+
+    #[test]
+    fn test_streamed_csv_sample() {
+        let path = std::env::temp_dir().join("datastory_sample.csv");
+        let mut csv = String::from("i,group\n");
+        for i in 0..500 {
+            csv.push_str(&format!("{},{}\n", i, if i % 2 == 0 { "even" } else { "odd" }));
+        }
+        std::fs::write(&path, csv).unwrap();
+        let path = path.to_str().unwrap();
+        for (sampling, rows, population) in [
+            (sampling(Some(SampleMethod::Reservoir), Some(30), None), 30, Some(500)),
+            (Sampling::new(None, Some(30), None, Some("group".to_string()), None).unwrap().unwrap(), 30, Some(500)),
+            // Reading stops after the head, so the row count is unknown
+            (Sampling::head(30), 30, None),
+        ] {
+            let options = ReadOptions {
+                sample: Some(sampling),
+                ..ReadOptions::default()
+            };
+            let dataset = read_dataset_with_options(path, &options).unwrap();
+            assert_eq!(dataset.rows.len(), rows);
+            assert_eq!(dataset.sample.unwrap().population, population);
+        }
+        let head = ReadOptions {
+            sample: Some(Sampling::head(30)),
+            ..ReadOptions::default()
+        };
+        let info = read_dataset_with_options(path, &head).unwrap().sample.unwrap();
+        assert_eq!(info.description(), "First 30 rows; the rest was not read");
+        // A head as long as the file is no sample
+        let whole = ReadOptions {
+            sample: Some(Sampling::head(500)),
+            ..ReadOptions::default()
+        };
+        assert_eq!(read_dataset_with_options(path, &whole).unwrap().sample, None);
+    }
+}
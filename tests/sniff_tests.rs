@@ -0,0 +1,57 @@
+// Unit tests for input format and CSV dialect detection
+use datastory::sniff::{InputFormat, SNIFF_LEN, detect_encoding, detect_format, sniff_csv_dialect};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(b"PAR1\x15\x04"), InputFormat::Parquet);
+        assert_eq!(detect_format(b"  [{\"a\": 1}]"), InputFormat::Json);
+        assert_eq!(detect_format(b"{\n  \"a\": 1\n}"), InputFormat::Json);
+        assert_eq!(detect_format(b"{\"a\": 1}\n{\"a\": 2}\n"), InputFormat::Ndjson);
+        assert_eq!(detect_format(b"\xef\xbb\xbfa,b\n1,2\n"), InputFormat::Csv);
+        assert_eq!(detect_format(b"PK\x03\x04\x14\x00"), InputFormat::Spreadsheet);
+        assert_eq!(detect_format(b""), InputFormat::Csv);
+    }
+
+    #[test]
+    fn test_input_format_from_str() {
+        assert_eq!("JSONL".parse::<InputFormat>(), Ok(InputFormat::Ndjson));
+        assert_eq!("tsv".parse::<InputFormat>(), Ok(InputFormat::Csv));
+        assert_eq!("ODS".parse::<InputFormat>(), Ok(InputFormat::Spreadsheet));
+        assert!("xml".parse::<InputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"caf\xc3\xa9").0.name(), "UTF-8");
+        // Multi-byte character cut off at the end of the prefix
+        let mut prefix = vec![b'a'; SNIFF_LEN - 1];
+        prefix.push(0xc3);
+        assert_eq!(detect_encoding(&prefix).0.name(), "UTF-8");
+        assert_eq!(detect_encoding(b"caf\xe9").0.name(), "windows-1252");
+        assert_eq!(detect_encoding(b"\xff\xfea\x00"), (encoding_rs::UTF_16LE, 2));
+    }
+
+    #[test]
+    fn test_sniff_delimiter_and_quote() {
+        let dialect = sniff_csv_dialect(b"a;b;c\n1,5;2;x\n3,5;4;y\n");
+        assert_eq!(dialect.delimiter, b';');
+        let dialect = sniff_csv_dialect(b"name\tnote\nann\t'a, b'\nbob\t'c'\n");
+        assert_eq!(dialect.delimiter, b'\t');
+        assert_eq!(dialect.quote, b'\'');
+        let dialect = sniff_csv_dialect(b"id|label\n1|\"x|y\"\n2|z\n");
+        assert_eq!(dialect.delimiter, b'|');
+        assert_eq!(dialect.quote, b'"');
+    }
+
+    #[test]
+    fn test_sniff_header() {
+        assert!(sniff_csv_dialect(b"id,score\n1,2.5\n2,3.5\n").has_headers);
+        assert!(!sniff_csv_dialect(b"1,2.5\n2,3.5\n").has_headers);
+        assert!(sniff_csv_dialect(b"city,country\nParis,France\n").has_headers);
+        assert!(!sniff_csv_dialect(b"x,x\ny,z\n").has_headers);
+    }
+}
@@ -0,0 +1,184 @@
+// Unit tests for the XLSX / ODS reader
+use datastory::formats::{ReadOptions, read_dataset, read_dataset_with_options, read_sheets};
+use std::io::Write;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+    const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+    /// A minimal XLSX workbook; each sheet is a list of rows of raw `<c>` contents
+    /// (`t` attribute, style and value) keyed by column letter
+    fn write_xlsx(name: &str, sheets: &[(&str, Vec<Vec<&str>>)]) -> String {
+        let path = std::env::temp_dir().join(name);
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        let mut put = |file: &str, content: String| {
+            zip.start_file(file, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        };
+        let overrides: String = (1..=sheets.len())
+            .map(|i| format!("<Override PartName='/xl/worksheets/sheet{}.xml' ContentType='application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml'/>", i))
+            .collect();
+        put("[Content_Types].xml", format!(
+            "<?xml version='1.0' encoding='UTF-8'?><Types xmlns='http://schemas.openxmlformats.org/package/2006/content-types'>\
+             <Default Extension='rels' ContentType='application/vnd.openxmlformats-package.relationships+xml'/>\
+             <Default Extension='xml' ContentType='application/xml'/>\
+             <Override PartName='/xl/workbook.xml' ContentType='application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml'/>\
+             <Override PartName='/xl/styles.xml' ContentType='application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml'/>{}</Types>",
+            overrides
+        ));
+        put("_rels/.rels", "<?xml version='1.0' encoding='UTF-8'?><Relationships xmlns='http://schemas.openxmlformats.org/package/2006/relationships'>\
+             <Relationship Id='rId1' Type='http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument' Target='xl/workbook.xml'/></Relationships>".to_string());
+        let sheet_list: String = sheets
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| format!("<sheet name='{}' sheetId='{}' r:id='rId{}'/>", name, i + 1, i + 1))
+            .collect();
+        put("xl/workbook.xml", format!(
+            "<?xml version='1.0' encoding='UTF-8'?><workbook xmlns='{}' xmlns:r='{}'><sheets>{}</sheets></workbook>",
+            MAIN_NS, REL_NS, sheet_list
+        ));
+        let rels: String = (1..=sheets.len())
+            .map(|i| format!("<Relationship Id='rId{}' Type='{}/worksheet' Target='worksheets/sheet{}.xml'/>", i, REL_NS, i))
+            .collect();
+        put("xl/_rels/workbook.xml.rels", format!(
+            "<?xml version='1.0' encoding='UTF-8'?><Relationships xmlns='http://schemas.openxmlformats.org/package/2006/relationships'>{}\
+             <Relationship Id='rIdStyles' Type='{}/styles' Target='styles.xml'/></Relationships>",
+            rels, REL_NS
+        ));
+        // Style 1 is the built-in short date format
+        put("xl/styles.xml", format!(
+            "<?xml version='1.0' encoding='UTF-8'?><styleSheet xmlns='{}'><cellXfs count='2'><xf numFmtId='0'/><xf numFmtId='14' applyNumberFormat='1'/></cellXfs></styleSheet>",
+            MAIN_NS
+        ));
+        for (i, (_, rows)) in sheets.iter().enumerate() {
+            let data: String = rows
+                .iter()
+                .enumerate()
+                .map(|(r, cells)| {
+                    let cells: String = cells
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, c)| !c.is_empty())
+                        .map(|(col, c)| format!("<c r='{}{}' {}</c>", (b'A' + col as u8) as char, r + 1, c))
+                        .collect();
+                    format!("<row r='{}'>{}</row>", r + 1, cells)
+                })
+                .collect();
+            put(&format!("xl/worksheets/sheet{}.xml", i + 1), format!(
+                "<?xml version='1.0' encoding='UTF-8'?><worksheet xmlns='{}'><sheetData>{}</sheetData></worksheet>",
+                MAIN_NS, data
+            ));
+        }
+        zip.finish().unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn text(s: &str) -> String {
+        format!("t='inlineStr'><is><t>{}</t></is>", s)
+    }
+
+    fn sales_workbook(name: &str) -> String {
+        let (title, region, amount, closed, day) =
+            (text("Quarterly sales"), text("region"), text("amount"), text("closed"), text("day"));
+        let (north, south) = (text("north"), text("south"));
+        write_xlsx(
+            name,
+            &[
+                (
+                    "Sales",
+                    vec![
+                        vec![&title],
+                        vec![],
+                        vec![&region, &amount, &closed, &day],
+                        vec![&north, "><v>1200.5</v>", "t='b'><v>1</v>", "s='1'><v>45292</v>"],
+                        vec![&south, "t='e'><v>#DIV/0!</v>", "t='b'><v>0</v>", "s='1'><v>45293</v>"],
+                    ],
+                ),
+                ("Empty", vec![]),
+                ("Raw", vec![vec!["><v>1</v>", "><v>2</v>"], vec!["><v>3</v>", "><v>4</v>"]]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_read_xlsx_detects_header_and_types() {
+        let path = sales_workbook("datastory_sales.xlsx");
+        let dataset = read_dataset(&path).unwrap();
+        assert_eq!(dataset.headers, vec!["region", "amount", "closed", "day"]);
+        assert_eq!(dataset.rows[0], vec!["north", "1200.5", "true", "2024-01-01"]);
+        // Error cells are missing values
+        assert_eq!(dataset.rows[1], vec!["south", "", "false", "2024-01-02"]);
+        assert_eq!(datastory::analyze::infer_column_type(&dataset.column("day").unwrap()), "date");
+        assert_eq!(datastory::analyze::infer_column_type(&dataset.column("closed").unwrap()), "boolean");
+    }
+
+    #[test]
+    fn test_read_all_sheets_or_one() {
+        let path = sales_workbook("datastory_sheets.xlsx");
+        let sheets = read_sheets(&path, &ReadOptions::default()).unwrap();
+        let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Sales", "Raw"]);
+        // Numbers only: no header row
+        assert_eq!(sheets[1].dataset.headers, vec!["column_1", "column_2"]);
+        assert_eq!(sheets[1].dataset.rows.len(), 2);
+
+        let options = ReadOptions {
+            sheet: Some("3".to_string()),
+            ..ReadOptions::default()
+        };
+        assert_eq!(read_dataset_with_options(&path, &options).unwrap().rows[1], vec!["3", "4"]);
+        let options = ReadOptions {
+            sheet: Some("Missing".to_string()),
+            ..ReadOptions::default()
+        };
+        assert!(read_dataset_with_options(&path, &options).is_err());
+    }
+
+    #[test]
+    fn test_sheets_report_has_a_section_per_sheet() {
+        let path = sales_workbook("datastory_sheets_report.xlsx");
+        let sheets = read_sheets(&path, &ReadOptions::default()).unwrap();
+        let report = std::env::temp_dir().join("datastory_sheets_report.md");
+        let options = datastory::report::HtmlOptions {
+            charts: false,
+            ..Default::default()
+        };
+        datastory::report::generate_sheets_report(
+            &sheets,
+            &path,
+            report.to_str().unwrap(),
+            datastory::templates::TemplateKind::Markdown,
+            &options,
+        )
+        .unwrap();
+        let markdown = std::fs::read_to_string(&report).unwrap();
+        assert!(markdown.contains("## Sheet `Sales`"));
+        assert!(markdown.contains("## Sheet `Raw`"));
+        assert!(markdown.contains("### Column `amount`"));
+        assert!(markdown.contains("### Column `column_2`"));
+    }
+
+    #[test]
+    fn test_single_sheet_formats_need_sheet() {
+        let path = sales_workbook("datastory_sheets_json.xlsx");
+        let report = std::env::temp_dir().join("datastory_sheets_report.json");
+        let report = report.to_str().unwrap();
+        let run = |extra: &[&str]| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_datastory"))
+                .args(["report", &path, report, "--format", "json"])
+                .args(extra)
+                .output()
+                .unwrap()
+        };
+        let refused = run(&[]);
+        assert!(!refused.status.success());
+        assert!(String::from_utf8_lossy(&refused.stderr).contains("pick it with --sheet"));
+        assert!(run(&["--sheet", "Raw"]).status.success());
+        let json = std::fs::read_to_string(report).unwrap();
+        assert!(json.contains("column_2"));
+    }
+}
@@ -0,0 +1,110 @@
+// Unit tests for the data quality assertions engine
+use datastory::formats::Dataset;
+use datastory::validate::{RuleSet, load_rules, validate_dataset};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn people() -> Dataset {
+        let rows = [
+            ["1", "34", "ann@example.com", "active"],
+            ["2", "130", "bob@example", "active"],
+            ["2", "", "carl@example.com", "paused"],
+            ["4", "-1", "", "inactive"],
+        ];
+        Dataset {
+            headers: ["id", "age", "email", "status"].iter().map(|s| s.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|r| r.iter().map(|c| c.to_string()).collect())
+                .collect(),
+            types: None,
+            numbers: None,
+            sample: None,
+        }
+    }
+
+    fn rules(toml: &str) -> RuleSet {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_all_checks() {
+        let rules = rules(
+            r#"
+            [[rules]]
+            column = "age"
+            between = [0, 120]
+            type = "numeric"
+
+            [[rules]]
+            column = "email"
+            matches = '^[^@\s]+@[^@\s]+\.[a-z]+$'
+            max_null_rate = 0.01
+
+            [[rules]]
+            column = "id"
+            unique = true
+
+            [[rules]]
+            column = "status"
+            in = ["active", "inactive"]
+            not_null = true
+            "#,
+        );
+        let report = validate_dataset(&people(), &rules, "people.csv").unwrap();
+        assert!(!report.passed);
+        let find = |column: &str, prefix: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.column == column && c.check.starts_with(prefix))
+                .unwrap()
+        };
+        let range = find("age", "between");
+        assert_eq!(range.failing_count, 2);
+        assert_eq!(range.failing_rows[0].row, 2);
+        assert_eq!(range.failing_rows[1].value, "-1");
+        assert!(find("age", "type").passed);
+        assert_eq!(find("email", "matches").failing_rows[0].value, "bob@example");
+        assert!(!find("email", "null rate").passed);
+        assert_eq!(find("id", "unique").failing_rows[0].row, 3);
+        assert_eq!(find("status", "in").failing_rows[0].value, "paused");
+        assert!(find("status", "not null").passed);
+        assert_eq!(report.failed_checks(), 5);
+    }
+
+    #[test]
+    fn test_passing_rules_and_missing_column() {
+        let ok = rules("[[rules]]\ncolumn = \"id\"\nmin = 1\n");
+        assert!(validate_dataset(&people(), &ok, "people.csv").unwrap().passed);
+        let missing = rules("[[rules]]\ncolumn = \"salary\"\nnot_null = true\n");
+        let report = validate_dataset(&people(), &missing, "people.csv").unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.checks[0].check, "column exists");
+    }
+
+    #[test]
+    fn test_matches_the_whole_value() {
+        let mut dataset = people();
+        dataset.rows[0][0] = "abc123456xyz".to_string();
+        dataset.rows[1][0] = "12345".to_string();
+        let zip = rules("[[rules]]\ncolumn = \"id\"\nmatches = '[0-9]{5}|[0-9]'\n");
+        let report = validate_dataset(&dataset, &zip, "people.csv").unwrap();
+        let failing: Vec<&str> = report.checks[0].failing_rows.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(failing, ["abc123456xyz"]);
+    }
+
+    #[test]
+    fn test_load_yaml_and_reject_unknown_keys() {
+        let dir = std::env::temp_dir();
+        let yaml = dir.join("datastory_rules.yaml");
+        std::fs::write(&yaml, "rules:\n  - column: status\n    in: [active, inactive]\n").unwrap();
+        let rules = load_rules(yaml.to_str().unwrap()).unwrap();
+        assert_eq!(rules.rules[0].allowed.as_ref().unwrap().len(), 2);
+        let toml = dir.join("datastory_rules_typo.toml");
+        std::fs::write(&toml, "[[rules]]\ncolumn = \"id\"\nuniq = true\n").unwrap();
+        assert!(load_rules(toml.to_str().unwrap()).is_err());
+    }
+}